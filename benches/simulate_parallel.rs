@@ -0,0 +1,24 @@
+use criterion::{ criterion_group, criterion_main, Criterion };
+use regex_executor::automaton::nfa::NFA;
+
+/// # (a|b)*abbを受理するNFAで, 同じ入力一覧を逐次と並列でシミュレートする
+fn bench_simulate_parallel(c: &mut Criterion) {
+    let nfa = NFA::concat(
+        NFA::kleene_star(NFA::for_char_set(&['a'..='b'])).ok().unwrap(),
+        NFA::for_literal("abb").ok().unwrap()
+    ).ok().unwrap();
+    let targets: Vec<String> = (0..20000u32)
+        .map(|n| format!("{:032b}", n.wrapping_mul(2654435761)).replace('0', "a").replace('1', "b"))
+        .collect();
+    let targets: Vec<&str> = targets.iter().map(|target| target.as_str()).collect();
+
+    c.bench_function("simulate_sequential", |b| {
+        b.iter(|| targets.iter().filter(|target| nfa.simulate(target.to_string())).count())
+    });
+    c.bench_function("simulate_parallel", |b| {
+        b.iter(|| nfa.simulate_parallel_count(&targets))
+    });
+}
+
+criterion_group!(benches, bench_simulate_parallel);
+criterion_main!(benches);
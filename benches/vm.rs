@@ -0,0 +1,25 @@
+use criterion::{ criterion_group, criterion_main, Criterion };
+use regex_executor::automaton::nfa::NFA;
+use regex_executor::automaton::vm::execute;
+
+/// # (a|b)*abbを受理するNFAで, 長い入力をHashSetによるシミュレートとバイトコードの実行で比較する
+fn bench_vm(c: &mut Criterion) {
+    let nfa = NFA::concat(
+        NFA::kleene_star(NFA::for_char_set(&['a'..='b'])).ok().unwrap(),
+        NFA::for_literal("abb").ok().unwrap()
+    ).ok().unwrap();
+    let program = nfa.compile_to_bytecode();
+    let target: String = (0..100000u32)
+        .map(|n| if n.wrapping_mul(2654435761) >> 31 == 0 { 'a' } else { 'b' })
+        .collect();
+
+    c.bench_function("simulate_hashset", |b| {
+        b.iter(|| nfa.simulate(target.clone()))
+    });
+    c.bench_function("simulate_bytecode", |b| {
+        b.iter(|| execute(&program, &target))
+    });
+}
+
+criterion_group!(benches, bench_vm);
+criterion_main!(benches);
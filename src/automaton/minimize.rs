@@ -0,0 +1,153 @@
+//! # 部分集合構成法によるNFAの決定化とBrzozowskiの方法による最小化
+
+#[cfg(feature = "std")]
+use std::collections::{ BTreeMap, VecDeque };
+#[cfg(not(feature = "std"))]
+use alloc::{ collections::{ BTreeMap, VecDeque }, vec, vec::Vec };
+use super::nfa::{ NFA, NFAError, NODE_LIMIT };
+
+/* 自身を引数にとるメソッド群 */
+impl NFA {
+    /// # 部分集合構成法で同じ言語を受理するDFAを生成する
+    ///
+    /// ## note
+    /// - DFAの状態は開始状態から見つけた順に0から番号を振る (開始状態は0). 空集合の状態 (行き止まり) は作らない
+    /// - ε遷移しか持たない状態 (受理状態を除く) は部分集合に含めない
+    /// - 受理状態が1つであればそれをそのまま受理状態とし, ε遷移を持たない
+    /// - 受理状態が複数 (または0個) であれば, 状態数と同じ番号の受理状態を追加して各受理状態からε遷移を張る
+    /// - 状態数がNODE_LIMITを超える場合はStateIndexOutOfRangeを返す
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn to_dfa(&self) -> Result<NFA, NFAError> {
+        let table = self.to_transition_table();
+        let mut alphabet: Vec<char> = table.iter().map(|(_, _, c)| *c).filter(|c| *c != '@').collect();
+        alphabet.sort();
+        alphabet.dedup();
+
+        // 文字による遷移を持たない状態は受理状態を除いて以降の遷移に影響しないため, 部分集合から除く
+        let mut active: Vec<i32> = table.iter().filter(|(_, _, c)| *c != '@').map(|(state_f, _, _)| *state_f).collect();
+        active.push(self.finish);
+        let sorted = |states: &mut Vec<i32>| {
+            states.retain(|state| active.contains(state));
+            states.sort();
+            states.dedup();
+        };
+        let mut init: Vec<i32> = self.simulate_state("").into_iter().collect();
+        sorted(&mut init);
+        let mut ids: BTreeMap<Vec<i32>, i32> = BTreeMap::new();
+        let mut queue: VecDeque<Vec<i32>> = VecDeque::new();
+        let mut dfa_table: Vec<(i32, i32, char)> = vec![];
+        let mut accepting: Vec<i32> = vec![];
+        ids.insert(init.clone(), 0);
+        queue.push_back(init);
+        while let Some(states) = queue.pop_front() {
+            let id = ids[&states];
+            if states.contains(&self.finish) {
+                accepting.push(id);
+            }
+            let states_set = states.iter().cloned().collect();
+            for c in &alphabet {
+                let mut next: Vec<i32> = self.state_image(&states_set, *c).into_iter().collect();
+                if next.is_empty() {
+                    continue;
+                }
+                sorted(&mut next);
+                let next_id = match ids.get(&next) {
+                    Some(next_id) => *next_id,
+                    None => {
+                        let next_id = ids.len() as i32;
+                        if next_id >= NODE_LIMIT as i32 {
+                            return Err(NFAError::StateIndexOutOfRange { state: next_id });
+                        }
+                        ids.insert(next.clone(), next_id);
+                        queue.push_back(next);
+                        next_id
+                    }
+                };
+                dfa_table.push((id, next_id, *c));
+            }
+        }
+
+        if accepting.len() == 1 {
+            return Self::from_transition_table(0, accepting[0], &dfa_table);
+        }
+        let finish = ids.len() as i32;
+        for state in accepting {
+            dfa_table.push((state, finish, '@'));
+        }
+        Self::from_transition_table(0, finish, &dfa_table)
+    }
+
+    /// # Brzozowskiの方法で状態数が最小のDFAを生成する
+    ///
+    /// ## note
+    /// - 反転, 決定化, 反転, 決定化の順に行う. 結果の状態番号と受理状態の扱いはto_dfaと同じ
+    /// - 行き止まりの状態を持たないDFAのうちで最小となる
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn minimize_states(&self) -> Result<NFA, NFAError> {
+        self.reverse().to_dfa()?.reverse().to_dfa()
+    }
+
+    /// # 状態数が既に最小かどうかを判定する
+    ///
+    /// ## note
+    /// trimした後の状態数がminimize_statesの結果の状態数と等しければtrue
+    ///
+    /// ## returns
+    /// bool
+    pub fn is_minimal(&self) -> bool {
+        match self.minimize_states() {
+            Ok(minimized) => minimized.state_count() == self.trim().state_count(),
+            Err(_) => false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NFA;
+    use crate::automaton::nfa::abb_nfa;
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_to_dfa() {
+        let mut nfa = NFA::new(0, 3).ok().unwrap();       // a(b|c)|ab
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(0, 2, 'a');
+        nfa.set_chain(1, 3, 'b');
+        nfa.set_chain(1, 3, 'c');
+        nfa.set_chain(2, 3, 'b');
+        let dfa = nfa.to_dfa().ok().unwrap();
+        assert!(dfa.is_deterministic());
+        assert_eq!(dfa.to_transition_table(), vec![(0, 1, 'a'), (1, 2, 'b'), (1, 2, 'c')]);
+        assert!(dfa.equivalent(&nfa));
+
+        let nfa = NFA::union(NFA::for_literal("a").ok().unwrap(), NFA::for_literal("ab").ok().unwrap()).ok().unwrap();
+        let dfa = nfa.to_dfa().ok().unwrap();
+        assert_eq!(dfa.to_transition_table(), vec![(0, 1, 'a'), (1, 2, 'b'), (1, 3, '@'), (2, 3, '@')]);
+        assert!(dfa.equivalent(&nfa));
+    }
+
+    #[test]
+    fn test_minimize_states() {
+        let nfa = abb_nfa();
+        let minimized = nfa.minimize_states().ok().unwrap();
+        assert_eq!(minimized.state_count(), 4);
+        assert!(minimized.is_deterministic());
+        assert!(minimized.equivalent(&nfa));
+        assert!(minimized.is_minimal());
+        assert!(!nfa.is_minimal());
+
+        let twice = minimized.minimize_states().ok().unwrap();
+        assert_eq!(twice.state_count(), 4);
+        assert!(twice.equivalent(&minimized));
+
+        let a_star = NFA::kleene_star(NFA::for_literal("a").ok().unwrap()).ok().unwrap();
+        let minimized = a_star.minimize_states().ok().unwrap();
+        assert_eq!(minimized.to_transition_table(), vec![(0, 0, 'a')]);
+        assert_eq!(minimized.start, minimized.finish);
+    }
+}
@@ -1,15 +1,33 @@
-use std::collections::{ HashSet, HashMap };
+#[cfg(feature = "std")]
+use std::collections::{ HashSet, HashMap, VecDeque };
+#[cfg(not(feature = "std"))]
+use alloc::collections::{ BTreeSet as HashSet, BTreeMap as HashMap, VecDeque };
+#[cfg(not(feature = "std"))]
+use alloc::{ boxed::Box, format, string::{ String, ToString }, vec, vec::Vec };
+use core::fmt::{ self, Write };
+use core::ops::RangeInclusive;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "ndarray-export")]
+use ndarray::{ Array3, ArrayView3 };
+#[cfg(feature = "unicode-normalization")]
+use unicode_normalization::UnicodeNormalization;
 
 /// # 定数
 ///
 /// - NODE_LIMIT: i32 => 管理できるノードの上限
-const NODE_LIMIT: usize = 1000;
+pub(crate) const NODE_LIMIT: usize = 1000;
+
+/// # キャプチャ付きシミュレートのスレッド (状態, グループごとの範囲)
+type CaptureThread = (i32, Vec<Option<(usize, usize)>>);
 
 /// # NFAのエラー
 #[derive(Debug, PartialEq)]
 pub enum NFAError {
     NonReservedState,
     AlreadyReservedState,
+    StateIndexOutOfRange { state: i32 },
+    DuplicateChain { from: i32, to: i32, c: char },
 }
 
 /// # NFA
@@ -17,30 +35,132 @@ pub enum NFAError {
 /// ## members
 /// - start: i32 => 開始状態
 /// - finish: i32 =>  受理状態
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct NFA {
     pub start: i32,
     pub finish: i32,
     reserved_state: Vec<bool>,
     move_table: HashMap<i32, HashMap<char, HashSet<i32>>>,
-    epsilon_chain: HashMap<i32, (HashSet<i32>, HashSet<i32>)>  // (forward, back)
+    epsilon_chain: HashMap<i32, (HashSet<i32>, HashSet<i32>)>, // (forward, back)
+    state_names: HashMap<i32, String>,
+    strict_mode: bool,
+    reversed: ReversedCache
+}
+
+/// # 反転したNFAのキャッシュ
+///
+/// ## note
+/// - simulate_anchored_endで初めて必要になった時に生成し, 遷移を変更するメソッドで破棄する
+/// - 比較やデバッグ出力ではキャッシュの有無を無視する
+#[derive(Clone, Default)]
+struct ReversedCache(OnceBox<NFA>);
+
+#[cfg(feature = "std")]
+type OnceBox<T> = std::sync::OnceLock<Box<T>>;
+#[cfg(not(feature = "std"))]
+type OnceBox<T> = core::cell::OnceCell<Box<T>>;
+
+/// # 逐次的なシミュレートの途中状態
+///
+/// ## note
+/// NFA::start_simulationで生成し, 文字を与えるたびに状態を更新する
+#[derive(Debug, Clone)]
+pub struct SimulationState<'a> {
+    nfa: &'a NFA,
+    states: HashSet<i32>
+}
+
+/// # 直積オートマトンの受理条件
+///
+/// ## variants
+/// - Both => 両方が受理する (積集合)
+/// - AOnly => aのみが受理する (差集合 a - b)
+/// - BOnly => bのみが受理する (差集合 b - a)
+/// - Either => 少なくとも一方が受理する (和集合)
+/// - ExactlyOne => ちょうど一方が受理する (対称差)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProductAccept {
+    Both,
+    AOnly,
+    BOnly,
+    Either,
+    ExactlyOne,
+}
+
+/// # シミュレートの結果とその理由
+///
+/// ## variants
+/// - Accepted => 受理された
+/// - DeadState => 途中で状態集合が空になった (position: その文字の位置 (バイト単位), character: その文字)
+/// - NoAcceptingState => 入力を読み終えたが受理状態に到達しなかった (active_states: 最後の状態集合 (昇順))
+#[derive(Debug, Clone, PartialEq)]
+pub enum RejectReason {
+    Accepted,
+    DeadState { position: usize, character: char },
+    NoAcceptingState { active_states: Vec<i32> },
+}
+
+/// # 上限付きシミュレートのエラー
+///
+/// ## variants
+/// - StepLimitExceeded => 処理した(状態, 文字位置)の組の数が上限に達した (steps_taken: 中断までに処理した数)
+#[derive(Debug, Clone, PartialEq)]
+pub enum SimulationError {
+    StepLimitExceeded { steps_taken: usize },
+}
+
+/// # Unicodeの正規化形式
+///
+/// ## variants
+/// - NFC => 正規分解の後に正規合成する
+/// - NFD => 正規分解する
+/// - NFKC => 互換分解の後に正規合成する
+/// - NFKD => 互換分解する
+#[cfg(feature = "unicode-normalization")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NormalizationForm {
+    NFC,
+    NFD,
+    NFKC,
+    NFKD,
+}
+
+/// # 対象文字列中で一致した部分
+///
+/// ## note
+/// NFA::do_matchで生成する. 位置はバイト単位
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Match<'t> {
+    text: &'t str,
+    start: usize,
+    end: usize
 }
 
 /* 自身を引数に取らない関数群 */
 impl NFA {
     /// # NFAのコンストラクタ
     ///
+    /// ## note
+    /// state_fを開始状態, state_tを受理状態とし, [state_f state_t]の状態を管理する
+    ///
     /// ## args
-    /// - init_states: Vec<i32> => 状態
-    pub fn new(state_f: i32, state_t: i32) -> NFA {
+    /// - state_f: i32 => 開始状態
+    /// - state_t: i32 => 受理状態
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn new(state_f: i32, state_t: i32) -> Result<NFA, NFAError> {
         let nfa = NFA {
             start: state_f,
             finish: state_t,
             move_table: HashMap::new(),
             epsilon_chain: HashMap::new(),
-            reserved_state: vec![false; NODE_LIMIT]
+            reserved_state: vec![],
+            state_names: HashMap::new(),
+            strict_mode: false,
+            reversed: ReversedCache::default()
         };
-        NFA::reserve(nfa, state_f, state_t).ok().unwrap()
+        NFA::reserve(nfa, state_f, state_t)
     }
 
     /// # NFAが管理する状態を追加する
@@ -48,68 +168,578 @@ impl NFA {
     /// ## note
     /// state_f/tは共に閉区間として扱われる
     /// [state_f state_t]
+    /// 範囲外(0未満またはNODE_LIMIT以上)の状態が含まれる場合はStateIndexOutOfRangeを返す
     ///
     /// ## args
     /// - nfa: NFA => 更新対象のNFA
     /// - state_f: i32 => 状態 (from)
-    /// _ state_t: i32 => 状態 (to)
+    /// - state_t: i32 => 状態 (to)
     ///
     /// ## return
     /// Result<NFA, NFAError>
     pub fn reserve(nfa: NFA, state_f: i32, state_t: i32) -> Result<NFA, NFAError> {
         let mut nfa = nfa;
-        for state in state_f..=state_t {
-            if nfa.reserved_state[state as usize] {
-                return Err(NFAError::AlreadyReservedState);
-            }
-            nfa.reserved_state[state as usize] = true;
-            nfa.move_table.insert(state, HashMap::new());
-            nfa.epsilon_chain.insert(state, (HashSet::new(), HashSet::new())); // (forward, back)
+        nfa.reserve_range(state_f, state_t)?;
+        Ok(nfa)
+    }
+
+    /// # NFAが管理する状態を1つ追加する
+    ///
+    /// ## note
+    /// 範囲外(0未満またはNODE_LIMIT以上)の状態であればStateIndexOutOfRangeを返す
+    ///
+    /// ## args
+    /// - nfa: NFA => 更新対象のNFA
+    /// - state: i32 => 状態
+    ///
+    /// ## return
+    /// Result<NFA, NFAError>
+    pub fn reserve_state(nfa: NFA, state: i32) -> Result<NFA, NFAError> {
+        NFA::reserve(nfa, state, state)
+    }
+
+    /// # NFAが管理する状態を飛び飛びに追加する
+    ///
+    /// ## note
+    /// - statesの先頭から順に1つずつ追加し, 既に管理している状態が現れた時点でAlreadyReservedStateを返す
+    /// - 範囲外(0未満またはNODE_LIMIT以上)の状態が現れた時点でStateIndexOutOfRangeを返す
+    ///
+    /// ## args
+    /// - nfa: NFA => 更新対象のNFA
+    /// - states: &[i32] => 状態一覧
+    ///
+    /// ## return
+    /// Result<NFA, NFAError>
+    pub fn reserve_states(nfa: NFA, states: &[i32]) -> Result<NFA, NFAError> {
+        let mut nfa = nfa;
+        for state in states {
+            nfa.reserve_range(*state, *state)?;
+        }
+        Ok(nfa)
+    }
+
+    /// # 遷移表からNFAを生成する
+    ///
+    /// ## note
+    /// - 遷移表および開始状態, 受理状態に現れる状態を全て管理対象にする
+    /// - 状態を管理する領域は, 現れる状態のうち最大のものに合わせて確保される
+    /// - 0未満またはNODE_LIMIT以上の状態が現れた場合はStateIndexOutOfRangeを返す
+    ///
+    /// ## args
+    /// - start: i32 => 開始状態
+    /// - finish: i32 => 受理状態
+    /// - table: &[(i32, i32, char)] => 遷移表 (遷移元状態, 遷移先状態, 文字)
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn from_transition_table(start: i32, finish: i32, table: &[(i32, i32, char)]) -> Result<NFA, NFAError> {
+        let mut states = vec![start, finish];
+        for (state_f, state_t, _) in table {
+            states.push(*state_f);
+            states.push(*state_t);
+        }
+        if let Some(state) = states.iter().find(|state| **state < 0 || **state >= NODE_LIMIT as i32) {
+            return Err(NFAError::StateIndexOutOfRange { state: *state });
+        }
+        states.sort();
+        states.dedup();
+
+        let mut nfa = NFA {
+            start,
+            finish,
+            move_table: HashMap::new(),
+            epsilon_chain: HashMap::new(),
+            reserved_state: Vec::with_capacity(*states.last().unwrap() as usize + 1),
+            state_names: HashMap::new(),
+            strict_mode: false,
+            reversed: ReversedCache::default()
+        };
+        for state in &states {
+            nfa.reserve_range(*state, *state)?;
+        }
+        for (state_f, state_t, c) in table {
+            nfa.set_chain(*state_f, *state_t, *c)?;
         }
         Ok(nfa)
     }
 
+    /// # 隣接行列からNFAを生成する
+    ///
+    /// ## note
+    /// - ndarray-exportフィーチャが必要
+    /// - matrix[[from, c_idx, to]]が0でない要素をfromからtoへのalphabet[c_idx]による遷移とみなす
+    /// - alphabetの範囲外の文字位置にある要素は無視する
+    ///
+    /// ## args
+    /// - start: i32 => 開始状態
+    /// - finish: i32 => 受理状態
+    /// - matrix: ArrayView3<u8> => 隣接行列 (遷移元状態, 文字位置, 遷移先状態)
+    /// - alphabet: &[char] => 文字位置に対応する文字の一覧
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    #[cfg(feature = "ndarray-export")]
+    pub fn from_adjacency_matrix(start: i32, finish: i32, matrix: ArrayView3<u8>, alphabet: &[char]) -> Result<NFA, NFAError> {
+        let table: Vec<(i32, i32, char)> = matrix.indexed_iter()
+            .filter(|((_, c_idx, _), value)| **value != 0 && *c_idx < alphabet.len())
+            .map(|((state_f, c_idx, state_t), _)| (state_f as i32, state_t as i32, alphabet[c_idx]))
+            .collect();
+        Self::from_transition_table(start, finish, &table)
+    }
+
     /// # NFA同士のマージ
     ///
+    /// ## note
+    /// nfa_aとnfa_bが管理する状態が重複している場合はAlreadyReservedStateを返す
+    ///
     /// ## args
     /// - nfa_a: NFA => 結合対象NFA A
     /// - nfa_b: NFA => 結合対象NFA B
     /// - merge_state_a: i32 => 結合する状態 A
-    /// - merge_state_b: i32 => 結合する状態B
+    /// - merge_state_b: i32 => 結合する状態B
     pub fn merge(nfa_a: NFA, nfa_b: NFA, merge_state_a: i32, merge_state_b: i32) -> Result<NFA, NFAError> {
-        // nfa_a拡張
+        if nfa_b.move_table.keys().any(|state| nfa_a.check_state(state)) {
+            return Err(NFAError::AlreadyReservedState);
+        }
         let mut nfa_a = nfa_a;
-        let mut reserve_s_f = -1;
-        for state in 0..(NODE_LIMIT as i32) {
-            match nfa_b.check_state(&state) {
-                true  if reserve_s_f < 0 => reserve_s_f = state,
-                false if reserve_s_f > 0 => {
-                    nfa_a = Self::reserve(nfa_a, reserve_s_f, state-1)?;
-                    reserve_s_f = -1;
+        let finish = nfa_b.finish;
+        nfa_a.absorb(nfa_b)?;
+        nfa_a.set_chain(merge_state_a, merge_state_b, '@')?;
+        nfa_a.finish = finish;
+        Ok(nfa_a)
+    }
+
+    /// # 文字集合のいずれか1文字を受理するNFAを生成する
+    ///
+    /// ## note
+    /// - '@'はε遷移を表すため, 範囲に含まれていても無視される
+    ///
+    /// ## args
+    /// - ranges: &[RangeInclusive<char>] => 受理する文字の範囲一覧
+    ///
+    /// ## returns
+    /// NFA
+    pub fn for_char_set(ranges: &[RangeInclusive<char>]) -> NFA {
+        let mut nfa = NFA::new(0, 1).ok().unwrap();
+        for range in ranges {
+            for c in range.clone() {
+                if c != '@' {
+                    nfa.set_chain(0, 1, c).ok().unwrap();
+                }
+            }
+        }
+        nfa
+    }
+
+    /// # 文字列sのみを受理するNFAを生成する
+    ///
+    /// ## note
+    /// - sに含まれる'@'はε遷移として扱われる
+    ///
+    /// ## args
+    /// - s: &str => 受理する文字列
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn for_literal(s: &str) -> Result<NFA, NFAError> {
+        let chars = s.chars().collect::<Vec<char>>();
+        let mut nfa = NFA::new(0, chars.len() as i32)?;
+        for (idx, c) in chars.iter().enumerate() {
+            nfa.set_chain(idx as i32, (idx+1) as i32, *c)?;
+        }
+        Ok(nfa)
+    }
+
+    /// # 文字列集合のいずれかを受理するNFAを生成する
+    ///
+    /// ## note
+    /// - 開始状態から受理状態まで, 単語ごとに独立した遷移の列を張る
+    /// - 単語に含まれる'@'はε遷移として扱われる
+    ///
+    /// ## args
+    /// - words: &[&str] => 受理する文字列一覧
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn for_keyword_set(words: &[&str]) -> Result<NFA, NFAError> {
+        let mut nfa = NFA::new(0, 1)?;
+        let mut next_state = 2;
+        for word in words {
+            let chars = word.chars().collect::<Vec<char>>();
+            if chars.is_empty() {
+                nfa.set_chain(0, 1, '@')?;
+                continue;
+            }
+            let inner = (chars.len() - 1) as i32;
+            nfa.reserve_range(next_state, next_state + inner - 1)?;
+            let mut states = vec![0];
+            states.extend(next_state..(next_state + inner));
+            states.push(1);
+            next_state += inner;
+            for (idx, c) in chars.iter().enumerate() {
+                nfa.set_chain(states[idx], states[idx+1], *c)?;
+            }
+        }
+        Ok(nfa)
+    }
+
+    /// # 文字列集合のいずれかを受理するトライ木の形のNFAを生成する
+    ///
+    /// ## note
+    /// - 共通の接頭辞ごとに1つの状態を共有するため, 状態数は (相異なる接頭辞の数 + 2) となる
+    /// - 各単語の終端の状態から受理状態へε遷移を張る
+    /// - 接頭辞の状態にはmax(start, finish)より大きい番号を割り当てる
+    /// - 単語に含まれる'@'はε遷移として扱われる
+    ///
+    /// ## args
+    /// - start: i32 => 開始状態
+    /// - finish: i32 => 受理状態
+    /// - words: &[&str] => 受理する文字列一覧
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn from_literal_set(start: i32, finish: i32, words: &[&str]) -> Result<NFA, NFAError> {
+        // 接頭辞ごとに状態を割り当てる
+        let base = start.max(finish) + 1;
+        let mut prefix_states: HashMap<String, i32> = HashMap::new();
+        for word in words {
+            for (idx, c) in word.char_indices() {
+                let prefix = &word[..idx + c.len_utf8()];
+                if !prefix_states.contains_key(prefix) {
+                    prefix_states.insert(prefix.to_string(), base + prefix_states.len() as i32);
+                }
+            }
+        }
+
+        let mut nfa = Self::reserve(NFA::new(start, start)?, finish, finish)?;
+        nfa.finish = finish;
+        if !prefix_states.is_empty() {
+            nfa = Self::reserve(nfa, base, base + prefix_states.len() as i32 - 1)?;
+        }
+        for word in words {
+            let mut state = start;
+            for (idx, c) in word.char_indices() {
+                let next_state = prefix_states[&word[..idx + c.len_utf8()]];
+                if !nfa.has_chain(state, next_state, c) {
+                    nfa.set_chain(state, next_state, c)?;
+                }
+                state = next_state;
+            }
+            if !nfa.has_chain(state, finish, '@') {
+                nfa.set_chain(state, finish, '@')?;
+            }
+        }
+        Ok(nfa)
+    }
+
+    /// # NFA同士の連接
+    ///
+    /// ## note
+    /// nfa_bの状態はnfa_aの状態と重複しないように振り直される
+    ///
+    /// ## args
+    /// - nfa_a: NFA => 前半のNFA
+    /// - nfa_b: NFA => 後半のNFA
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn concat(nfa_a: NFA, nfa_b: NFA) -> Result<NFA, NFAError> {
+        let offset = nfa_a.max_state() + 1 - nfa_b.min_state();
+        let nfa_b = Self::renumber(nfa_b, offset)?;
+        let (merge_state_a, merge_state_b) = (nfa_a.finish, nfa_b.start);
+        Self::merge(nfa_a, nfa_b, merge_state_a, merge_state_b)
+    }
+
+    /// # NFAのクリーネ閉包
+    ///
+    /// ## note
+    /// 新しい開始状態と受理状態を追加する (Thompsonの構成法)
+    ///
+    /// ## args
+    /// - base: NFA => 対象NFA
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn kleene_star(base: NFA) -> Result<NFA, NFAError> {
+        let (start, finish) = (base.max_state() + 1, base.max_state() + 2);
+        let mut nfa = Self::reserve(base, start, finish)?;
+        nfa.set_chain(start, nfa.start, '@')?;
+        nfa.set_chain(nfa.finish, nfa.start, '@')?;
+        nfa.set_chain(nfa.finish, finish, '@')?;
+        nfa.set_chain(start, finish, '@')?;
+        nfa.start = start;
+        nfa.finish = finish;
+        Ok(nfa)
+    }
+
+    /// # NFAの1回以上の繰り返し
+    ///
+    /// ## note
+    /// kleene_starから新しい開始状態と受理状態を直接結ぶε遷移を除いたもの
+    ///
+    /// ## args
+    /// - base: NFA => 対象NFA
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn one_or_more(base: NFA) -> Result<NFA, NFAError> {
+        let (start, finish) = (base.max_state() + 1, base.max_state() + 2);
+        let mut nfa = Self::reserve(base, start, finish)?;
+        nfa.set_chain(start, nfa.start, '@')?;
+        nfa.set_chain(nfa.finish, nfa.start, '@')?;
+        nfa.set_chain(nfa.finish, finish, '@')?;
+        nfa.start = start;
+        nfa.finish = finish;
+        Ok(nfa)
+    }
+
+    /// # NFA同士の選択 (和)
+    ///
+    /// ## note
+    /// nfa_bの状態はnfa_aの状態と重複しないように振り直される
+    ///
+    /// ## args
+    /// - nfa_a: NFA => 選択肢A
+    /// - nfa_b: NFA => 選択肢B
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn union(nfa_a: NFA, nfa_b: NFA) -> Result<NFA, NFAError> {
+        let offset = nfa_a.max_state() + 1 - nfa_b.min_state();
+        let nfa_b = Self::renumber(nfa_b, offset)?;
+        let (start_b, finish_b) = (nfa_b.start, nfa_b.finish);
+        let mut nfa = nfa_a;
+        nfa.absorb(nfa_b)?;
+        let (start, finish) = (nfa.max_state() + 1, nfa.max_state() + 2);
+        nfa = Self::reserve(nfa, start, finish)?;
+        nfa.set_chain(start, nfa.start, '@')?;
+        nfa.set_chain(start, start_b, '@')?;
+        nfa.set_chain(nfa.finish, finish, '@')?;
+        nfa.set_chain(finish_b, finish, '@')?;
+        nfa.start = start;
+        nfa.finish = finish;
+        Ok(nfa)
+    }
+
+    /// # NFAの省略可能化 (0回または1回)
+    ///
+    /// ## note
+    /// 新しい開始状態と受理状態を追加する (Thompsonの構成法)
+    ///
+    /// ## args
+    /// - base: NFA => 対象NFA
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn optional(base: NFA) -> Result<NFA, NFAError> {
+        let (start, finish) = (base.max_state() + 1, base.max_state() + 2);
+        let mut nfa = Self::reserve(base, start, finish)?;
+        nfa.set_chain(start, nfa.start, '@')?;
+        nfa.set_chain(nfa.finish, finish, '@')?;
+        nfa.set_chain(start, finish, '@')?;
+        nfa.start = start;
+        nfa.finish = finish;
+        Ok(nfa)
+    }
+
+    /// # ':'で区切られた項目の並び (item(:item)*) を受理するNFAを生成する
+    ///
+    /// ## note
+    /// PATHやCLASSPATHのような環境変数の形式で, 項目は1つ以上並ぶ
+    ///
+    /// ## args
+    /// - item_nfa: NFA => 項目1つを受理するNFA
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn for_colon_list(item_nfa: NFA) -> Result<NFA, NFAError> {
+        let rest = Self::kleene_star(Self::concat(Self::for_literal(":")?, item_nfa.clone())?)?;
+        Self::concat(item_nfa, rest)
+    }
+
+    /// # NFAをちょうどn回繰り返したものを受理するNFAを生成する
+    ///
+    /// ## args
+    /// - base: &NFA => 繰り返すNFA
+    /// - n: usize => 繰り返し回数
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn for_repeat(base: &NFA, n: usize) -> Result<NFA, NFAError> {
+        Self::for_range_repeat(base, n, n)
+    }
+
+    /// # NFAをmin回以上max回以下繰り返したものを受理するNFAを生成する
+    ///
+    /// ## note
+    /// baseをmax個連接し, min個目以降の各コピーの終端から新しい受理状態へε遷移を張る.
+    /// そのため状態数はおよそ (baseの状態数 * max) で済む
+    /// min > maxの場合は何も受理しない
+    ///
+    /// ## args
+    /// - base: &NFA => 繰り返すNFA
+    /// - min: usize => 最小繰り返し回数
+    /// - max: usize => 最大繰り返し回数
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn for_range_repeat(base: &NFA, min: usize, max: usize) -> Result<NFA, NFAError> {
+        let mut nfa = NFA::new(0, 0)?;
+        let mut ends = vec![nfa.start];
+        for _ in 0..max {
+            nfa = Self::concat(nfa, base.clone())?;
+            ends.push(nfa.finish);
+        }
+        let finish = nfa.max_state() + 1;
+        nfa.reserve_range(finish, finish)?;
+        for end in ends.iter().skip(min) {
+            nfa.set_chain(*end, finish, '@')?;
+        }
+        nfa.finish = finish;
+        Ok(nfa)
+    }
+
+    /// # 正規表現の量指定子 ({n}, {min,max}, {min,}) に相当するNFAを生成する
+    ///
+    /// ## note
+    /// - maxがSomeの場合はfor_range_repeatと同じ
+    /// - maxがNoneの場合はbaseをmin個連接した後ろにbaseのクリーネ閉包を続ける
+    ///
+    /// ## args
+    /// - base: NFA => 繰り返すNFA
+    /// - min: usize => 最小繰り返し回数
+    /// - max: Option<usize> => 最大繰り返し回数 (Noneなら上限なし)
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn quantifier(base: NFA, min: usize, max: Option<usize>) -> Result<NFA, NFAError> {
+        match max {
+            Some(max) => Self::for_range_repeat(&base, min, max),
+            None => Self::concat(Self::for_repeat(&base, min)?, Self::kleene_star(base)?)
+        }
+    }
+
+    /// # 2つのNFAの直積オートマトンを生成する
+    ///
+    /// ## note
+    /// - 両方のNFAを部分集合構成法で決定化しながら, (aの状態集合, bの状態集合)の組を
+    ///   開始状態から幅優先で探索し, 発見した順に0から状態番号を割り当てる
+    /// - 決定化しているため, 差集合や対称差のように「受理しない」ことを条件とする場合も正しく扱える
+    /// - 状態の組ではなく状態集合の組を状態とするため, 結果の状態数は入力の状態数に対して指数的に増えうる
+    ///   ((a|b)*a(a|b){n}のように決定化で状態数が爆発するNFAでは, 入力が小さくてもStateIndexOutOfRangeとなる)
+    /// - 受理条件を満たす組から新しい受理状態へε遷移を張る
+    /// - どちらの状態集合も空になった組は何も受理しないため省略する
+    ///
+    /// ## args
+    /// - a: &NFA => NFA A
+    /// - b: &NFA => NFA B
+    /// - accept: ProductAccept => 受理条件
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError> => 状態数がNODE_LIMITを超える場合はStateIndexOutOfRange
+    pub fn product_construction(a: &NFA, b: &NFA, accept: ProductAccept) -> Result<NFA, NFAError> {
+        let mut alphabet: Vec<char> = vec![];
+        for nfa in &[a, b] {
+            for chars in nfa.move_table.values() {
+                alphabet.extend(chars.keys().filter(|c| **c != '@'));
+            }
+        }
+        alphabet.sort();
+        alphabet.dedup();
+
+        let sorted = |states: HashSet<i32>| {
+            let mut states = states.into_iter().collect::<Vec<i32>>();
+            states.sort();
+            states
+        };
+        let init = (sorted(a.initial_states()), sorted(b.initial_states()));
+        let mut ids: HashMap<(Vec<i32>, Vec<i32>), i32> = HashMap::new();
+        let mut queue: VecDeque<(Vec<i32>, Vec<i32>)> = VecDeque::new();
+        let mut table: Vec<(i32, i32, char)> = vec![];
+        let mut accepting: Vec<i32> = vec![];
+        ids.insert(init.clone(), 0);
+        queue.push_back(init);
+        while let Some((states_a, states_b)) = queue.pop_front() {
+            let id = ids[&(states_a.clone(), states_b.clone())];
+            if accept.accepts(states_a.contains(&a.finish), states_b.contains(&b.finish)) {
+                accepting.push(id);
+            }
+            let states_a_set = states_a.iter().cloned().collect::<HashSet<i32>>();
+            let states_b_set = states_b.iter().cloned().collect::<HashSet<i32>>();
+            for c in &alphabet {
+                let next = (
+                    sorted(a.state_image(&states_a_set, *c)),
+                    sorted(b.state_image(&states_b_set, *c))
+                );
+                if next.0.is_empty() && next.1.is_empty() {
+                    continue;
                 }
-                _ => {}
+                let next_id = match ids.get(&next) {
+                    Some(next_id) => *next_id,
+                    None => {
+                        let next_id = ids.len() as i32;
+                        if next_id >= NODE_LIMIT as i32 {
+                            return Err(NFAError::StateIndexOutOfRange { state: next_id });
+                        }
+                        ids.insert(next.clone(), next_id);
+                        queue.push_back(next);
+                        next_id
+                    }
+                };
+                table.push((id, next_id, *c));
             }
         }
-        if reserve_s_f > 0 {
-            nfa_a = Self::reserve(nfa_a, reserve_s_f, (NODE_LIMIT-1) as i32)?;
+
+        let finish = ids.len() as i32;
+        for state in accepting {
+            table.push((state, finish, '@'));
         }
+        Self::from_transition_table(0, finish, &table)
+    }
 
-        // nfa_bに登録済のパスをnfa_aに登録していく
-        for (state_f, chars) in nfa_b.move_table.iter() {
+    /// # NFAが管理する状態全てをoffsetだけずらしたNFAを生成する
+    fn renumber(nfa: NFA, offset: i32) -> Result<NFA, NFAError> {
+        let mut renumbered = NFA {
+            start: nfa.start + offset,
+            finish: nfa.finish + offset,
+            move_table: HashMap::new(),
+            epsilon_chain: HashMap::new(),
+            reserved_state: vec![],
+            state_names: HashMap::new(),
+            strict_mode: nfa.strict_mode,
+            reversed: ReversedCache::default()
+        };
+        for state in nfa.move_table.keys() {
+            renumbered = Self::reserve(renumbered, state + offset, state + offset)?;
+        }
+        for (state_f, chars) in nfa.move_table.iter() {
             for (c, state_t_list) in chars.iter() {
                 for state_t in state_t_list {
-                    nfa_a.set_chain(*state_f, *state_t, *c)?;
+                    renumbered.set_chain(state_f + offset, state_t + offset, *c)?;
                 }
             }
         }
-        nfa_a.set_chain(merge_state_a, merge_state_b, '@')?;
-        nfa_a.finish = nfa_b.finish;
-        Ok(nfa_a)
+        for (state, name) in nfa.state_names.iter() {
+            renumbered.state_names.insert(state + offset, name.clone());
+        }
+        Ok(renumbered)
     }
 }
 
 /* 自身を引数にとるメソッド群 */
 impl NFA {
+    /// # 厳格モードを有効にしたNFAを返す
+    ///
+    /// ## note
+    /// 厳格モードでは, 既に存在する遷移をset_chainで追加しようとするとDuplicateChainを返す
+    ///
+    /// ## returns
+    /// NFA
+    pub fn with_strict_mode(mut self) -> Self {
+        self.strict_mode = true;
+        self
+    }
+
     /// # 状態S1と状態S2を文字Cで繋ぐ
     ///
     /// # note
@@ -121,11 +751,16 @@ impl NFA {
     /// - c: 文字C
     ///
     /// ## returns
-    /// Result<(), ()>
+    /// Result<(), NFAError> => 状態S1, S2のいずれかが管理されていなければNonReservedState,
+    /// 厳格モードで既に同じ遷移が存在すればDuplicateChain
     pub fn set_chain(&mut self, state_a: i32, state_b: i32, c: char) -> Result<(), NFAError> {
         if !(Self::check_state(self, &state_a) && Self::check_state(self, &state_b)) {
             return Err(NFAError::NonReservedState)
         }
+        if self.strict_mode && Self::has_chain(self, state_a, state_b, c) {
+            return Err(NFAError::DuplicateChain { from: state_a, to: state_b, c });
+        }
+        self.reversed.clear();
         // 遷移表更新
         if !self.move_table[&state_a].contains_key(&c) {
             self.move_table.get_mut(&state_a).unwrap()
@@ -141,7 +776,494 @@ impl NFA {
         Ok(())
     }
 
-    /// # オートマトンのシミュレートを行う
+    /// # 他のNFAをその場でマージする
+    ///
+    /// ## note
+    /// - otherの状態がselfの状態と重複する場合, otherの状態は重複しないように振り直される
+    /// - merge_state_selfからmerge_state_otherへε遷移を追加し, 受理状態はotherの受理状態になる
+    ///
+    /// ## args
+    /// - other: NFA => 取り込むNFA
+    /// - merge_state_self: i32 => 結合する状態 (self側)
+    /// - merge_state_other: i32 => 結合する状態 (other側, 振り直し前の番号)
+    ///
+    /// ## returns
+    /// Result<(), NFAError>
+    pub fn merge_into(&mut self, other: NFA, merge_state_self: i32, merge_state_other: i32) -> Result<(), NFAError> {
+        if !(Self::check_state(self, &merge_state_self) && other.check_state(&merge_state_other)) {
+            return Err(NFAError::NonReservedState)
+        }
+        let mut other = other;
+        let mut offset = 0;
+        if other.move_table.keys().any(|state| Self::check_state(self, state)) {
+            offset = self.max_state() + 1 - other.min_state();
+            other = Self::renumber(other, offset)?;
+        }
+        let finish = other.finish;
+        self.reversed.clear();
+        self.absorb(other)?;
+        self.set_chain(merge_state_self, merge_state_other + offset, '@')?;
+        self.finish = finish;
+        Ok(())
+    }
+
+    /// # 遷移表に変換する
+    ///
+    /// ## note
+    /// (遷移元状態, 遷移先状態, 文字)の組を昇順に並べて返す. ε遷移の文字は'@'となる
+    ///
+    /// ## returns
+    /// Vec<(i32, i32, char)>
+    pub fn to_transition_table(&self) -> Vec<(i32, i32, char)> {
+        let mut table: Vec<(i32, i32, char)> = vec![];
+        for (state_f, chars) in self.move_table.iter() {
+            for (c, state_t_list) in chars.iter() {
+                for state_t in state_t_list {
+                    table.push((*state_f, *state_t, *c));
+                }
+            }
+        }
+        table.sort();
+        table
+    }
+
+    /// # 隣接行列に変換する
+    ///
+    /// ## note
+    /// - ndarray-exportフィーチャが必要
+    /// - 形は(状態を管理する領域の大きさ, alphabetの文字数, 状態を管理する領域の大きさ)
+    /// - fromからtoへalphabet[c_idx]で遷移できるとき[[from, c_idx, to]]が1, それ以外は0. alphabetに含まれない文字の遷移は無視する
+    ///
+    /// ## args
+    /// - alphabet: &[char] => 文字位置に対応する文字の一覧 (ε遷移を含める場合は'@')
+    ///
+    /// ## returns
+    /// Array3<u8>
+    #[cfg(feature = "ndarray-export")]
+    pub fn to_adjacency_matrix(&self, alphabet: &[char]) -> Array3<u8> {
+        let size = self.reserved_state.len();
+        let mut matrix = Array3::zeros((size, alphabet.len(), size));
+        for (state_f, state_t, c) in self.to_transition_table() {
+            if let Some(c_idx) = alphabet.iter().position(|a| *a == c) {
+                matrix[[state_f as usize, c_idx, state_t as usize]] = 1;
+            }
+        }
+        matrix
+    }
+
+    /// # 受理する言語を反転したNFAを生成する
+    ///
+    /// ## note
+    /// 全ての遷移 (ε遷移を含む) の向きを逆にし, 開始状態と受理状態を入れ替える
+    ///
+    /// ## returns
+    /// NFA
+    pub fn reverse(&self) -> NFA {
+        let mut nfa = NFA {
+            start: self.finish,
+            finish: self.start,
+            move_table: HashMap::new(),
+            epsilon_chain: HashMap::new(),
+            reserved_state: Vec::with_capacity(self.reserved_state.len()),
+            state_names: self.state_names.clone(),
+            strict_mode: self.strict_mode,
+            reversed: ReversedCache::default()
+        };
+        for state in self.move_table.keys() {
+            nfa.reserve_range(*state, *state).ok().unwrap();
+        }
+        for (state_f, state_t, c) in self.to_transition_table() {
+            nfa.set_chain(state_t, state_f, c).ok().unwrap();
+        }
+        nfa
+    }
+
+    /// # 対応表に従って状態番号を付け替えたNFAを生成する
+    ///
+    /// ## note
+    /// - 開始状態, 受理状態, 遷移, 状態名の全てを付け替える
+    /// - 管理している状態が対応表に含まれていなければNonReservedStateを返す
+    /// - 付け替え先が重複していればAlreadyReservedState, 範囲外であればStateIndexOutOfRangeを返す
+    ///
+    /// ## args
+    /// - mapping: &HashMap<i32, i32> => 付け替え前の状態から付け替え後の状態への対応表
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn rename_states(&self, mapping: &HashMap<i32, i32>) -> Result<NFA, NFAError> {
+        let rename = |state: &i32| mapping.get(state).cloned().ok_or(NFAError::NonReservedState);
+        let mut nfa = NFA {
+            start: rename(&self.start)?,
+            finish: rename(&self.finish)?,
+            move_table: HashMap::new(),
+            epsilon_chain: HashMap::new(),
+            reserved_state: vec![],
+            state_names: HashMap::new(),
+            strict_mode: self.strict_mode,
+            reversed: ReversedCache::default()
+        };
+        for state in self.move_table.keys() {
+            let state = rename(state)?;
+            nfa.reserve_range(state, state)?;
+        }
+        for (state_f, state_t, c) in self.to_transition_table() {
+            nfa.set_chain(rename(&state_f)?, rename(&state_t)?, c)?;
+        }
+        for (state, name) in self.state_names.iter() {
+            nfa.state_names.insert(rename(state)?, name.clone());
+        }
+        Ok(nfa)
+    }
+
+    /// # 全ての状態番号をoffsetだけずらしたNFAを生成する
+    ///
+    /// ## note
+    /// ずらした先が範囲外(0未満またはNODE_LIMIT以上)になればStateIndexOutOfRangeを返す
+    ///
+    /// ## args
+    /// - offset: i32 => ずらす量
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn rename_states_offset(&self, offset: i32) -> Result<NFA, NFAError> {
+        Self::renumber(self.clone(), offset)
+    }
+
+    /// # ASCII文字の大文字小文字を区別しないように遷移を追加する
+    ///
+    /// ## note
+    /// ASCIIの英字による遷移全てについて, 大文字と小文字を入れ替えた文字による遷移を追加する
+    ///
+    /// ## returns
+    /// Result<(), NFAError>
+    pub fn build_case_insensitive(&mut self) -> Result<(), NFAError> {
+        for (state_f, state_t, c) in self.to_transition_table() {
+            let swapped = if c.is_ascii_uppercase() { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() };
+            if c.is_ascii_alphabetic() && !self.has_chain(state_f, state_t, swapped) {
+                self.set_chain(state_f, state_t, swapped)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// # 開始状態から到達できる状態 (開始状態自身を含む) を返す
+    ///
+    /// ## returns
+    /// HashSet<i32>
+    pub fn reachable_states(&self) -> HashSet<i32> {
+        let mut visited: HashSet<i32> = HashSet::new();
+        let mut queue: VecDeque<i32> = VecDeque::new();
+        visited.insert(self.start);
+        queue.push_back(self.start);
+        while let Some(state) = queue.pop_front() {
+            for state_t_list in self.move_table[&state].values() {
+                for state_t in state_t_list {
+                    if visited.insert(*state_t) {
+                        queue.push_back(*state_t);
+                    }
+                }
+            }
+        }
+        visited
+    }
+
+    /// # 受理状態へ到達できる状態 (受理状態自身を含む) を返す
+    ///
+    /// ## returns
+    /// HashSet<i32>
+    pub fn productive_states(&self) -> HashSet<i32> {
+        let mut back_table: HashMap<i32, Vec<i32>> = HashMap::new();
+        for (state_f, state_t, _) in self.to_transition_table() {
+            back_table.entry(state_t).or_default().push(state_f);
+        }
+        let mut visited: HashSet<i32> = HashSet::new();
+        let mut queue: VecDeque<i32> = VecDeque::new();
+        visited.insert(self.finish);
+        queue.push_back(self.finish);
+        while let Some(state) = queue.pop_front() {
+            for state_f in back_table.get(&state).into_iter().flatten() {
+                if visited.insert(*state_f) {
+                    queue.push_back(*state_f);
+                }
+            }
+        }
+        visited
+    }
+
+    /// # 受理状態へ到達できない状態 (行き止まりの状態) を返す
+    ///
+    /// ## note
+    /// - 受理状態から遷移を逆に辿って到達できない, 管理している状態を昇順に並べる (productive_statesの補集合)
+    /// - 受理状態自身は含まない
+    ///
+    /// ## returns
+    /// Vec<i32>
+    pub fn dead_states(&self) -> Vec<i32> {
+        let productive_states = self.productive_states();
+        let mut states: Vec<i32> = self.move_table.keys()
+            .filter(|state| !productive_states.contains(state))
+            .cloned()
+            .collect();
+        states.sort();
+        states
+    }
+
+    /// # 受理状態へ到達できない状態を持つかどうかを判定する
+    ///
+    /// ## returns
+    /// bool
+    pub fn has_dead_states(&self) -> bool {
+        let productive_states = self.productive_states();
+        self.move_table.keys().any(|state| !productive_states.contains(state))
+    }
+
+    /// # 一度入ると抜け出せない状態 (トラップ状態) を返す
+    ///
+    /// ## note
+    /// - 受理状態以外で, 自身への遷移しか持たない (遷移を持たない場合も含む) 状態
+    /// - 複数ある場合は番号が最も小さいものを返す
+    ///
+    /// ## returns
+    /// Option<i32>
+    pub fn sink_state(&self) -> Option<i32> {
+        self.move_table.iter()
+            .filter(|(state, chars)| {
+                **state != self.finish && chars.values().all(|state_t_list| state_t_list.iter().all(|state_t| state_t == *state))
+            })
+            .map(|(state, _)| *state)
+            .min()
+    }
+
+    /// # 決定性 (DFAと同じ形) かどうかを判定する
+    ///
+    /// ## note
+    /// ε遷移を持たず, 全ての状態と文字について遷移先が高々1つであればtrue (遷移先のない文字があってもよい)
+    ///
+    /// ## returns
+    /// bool
+    pub fn is_deterministic(&self) -> bool {
+        self.epsilon_chain.values().all(|(forward, _)| forward.is_empty())
+            && self.move_table.values().all(|chars| chars.values().all(|state_t_list| state_t_list.len() <= 1))
+    }
+
+    /// # 決定性であれば自身への参照を返す
+    ///
+    /// ## note
+    /// DFA向けの高速な処理に切り替える際の判定に使う
+    ///
+    /// ## returns
+    /// Option<&NFA> => 決定性でなければNone
+    pub fn as_deterministic(&self) -> Option<&NFA> {
+        if self.is_deterministic() {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    /// # 到達できない状態と受理状態へ到達できない状態を取り除いたNFAを生成する
+    ///
+    /// ## note
+    /// - 残った状態の番号と名前はそのまま引き継ぐ
+    /// - 開始状態と受理状態は常に残す
+    ///
+    /// ## returns
+    /// NFA
+    pub fn trim(&self) -> NFA {
+        let productive_states = self.productive_states();
+        let mut states: HashSet<i32> = self.reachable_states()
+            .into_iter()
+            .filter(|state| productive_states.contains(state))
+            .collect();
+        states.insert(self.start);
+        states.insert(self.finish);
+
+        let mut nfa = NFA {
+            start: self.start,
+            finish: self.finish,
+            move_table: HashMap::new(),
+            epsilon_chain: HashMap::new(),
+            reserved_state: Vec::with_capacity(self.reserved_state.len()),
+            state_names: self.state_names.iter()
+                .filter(|(state, _)| states.contains(state))
+                .map(|(state, name)| (*state, name.clone()))
+                .collect(),
+            strict_mode: self.strict_mode,
+            reversed: ReversedCache::default()
+        };
+        for state in &states {
+            nfa.reserve_range(*state, *state).ok().unwrap();
+        }
+        for (state_f, state_t, c) in self.to_transition_table() {
+            if states.contains(&state_f) && states.contains(&state_t) {
+                nfa.set_chain(state_f, state_t, c).ok().unwrap();
+            }
+        }
+        nfa
+    }
+
+    /// # 受理する言語が有限かどうかを判定する
+    ///
+    /// ## note
+    /// - trimした後の遷移グラフに, 文字による遷移を1つ以上含む閉路があれば無限
+    /// - ε遷移のみからなる閉路は受理する文字列を増やさないため無視する
+    /// - 何も受理しない場合は有限 (true) とする
+    ///
+    /// ## returns
+    /// bool
+    pub fn language_is_finite(&self) -> bool {
+        if !self.reachable_states().contains(&self.finish) {
+            return true;
+        }
+        let nfa = self.trim();
+        let table = nfa.to_transition_table();
+        let reaches = |from: i32, to: i32| {
+            let mut visited: HashSet<i32> = HashSet::new();
+            let mut stack = vec![from];
+            while let Some(state) = stack.pop() {
+                if state == to {
+                    return true;
+                }
+                if visited.insert(state) {
+                    stack.extend(table.iter().filter(|(state_f, _, _)| *state_f == state).map(|(_, state_t, _)| *state_t));
+                }
+            }
+            false
+        };
+        !table.iter().any(|(state_f, state_t, c)| *c != '@' && reaches(*state_t, *state_f))
+    }
+
+    /// # 受理する言語が無限かどうかを判定する
+    ///
+    /// ## returns
+    /// bool
+    pub fn language_is_infinite(&self) -> bool {
+        !self.language_is_finite()
+    }
+
+    /// # 管理している状態の数を返す
+    ///
+    /// ## returns
+    /// usize
+    pub fn state_count(&self) -> usize {
+        self.move_table.len()
+    }
+
+    /// # 状態に名前を付ける
+    ///
+    /// ## args
+    /// - state: i32 => 名前を付ける状態
+    /// - name: &str => 名前
+    ///
+    /// ## returns
+    /// Result<(), NFAError>
+    pub fn name_state(&mut self, state: i32, name: &str) -> Result<(), NFAError> {
+        if !Self::check_state(self, &state) {
+            return Err(NFAError::NonReservedState)
+        }
+        self.state_names.insert(state, name.to_string());
+        Ok(())
+    }
+
+    /// # 状態に付けられた名前を返す
+    ///
+    /// ## args
+    /// - state: i32 => 対象の状態
+    ///
+    /// ## returns
+    /// Option<&str>
+    pub fn state_name(&self, state: i32) -> Option<&str> {
+        self.state_names.get(&state).map(|name| name.as_str())
+    }
+
+    /// # DOT言語(Graphviz)形式の文字列に変換する
+    ///
+    /// ## note
+    /// - 名前が付けられた状態はその名前を, それ以外は状態番号をラベルとして出力する
+    /// - ε遷移のラベルは"ε"として出力する
+    ///
+    /// ## returns
+    /// String
+    pub fn to_dot(&self) -> String {
+        let mut states = self.move_table.keys().cloned().collect::<Vec<i32>>();
+        states.sort();
+
+        let mut dot = String::from("digraph NFA {\n    rankdir=LR;\n    start [shape=point];\n");
+        for state in &states {
+            let label = match self.state_name(*state) {
+                Some(name) => name.replace('\\', "\\\\").replace('"', "\\\""),
+                None => state.to_string()
+            };
+            let shape = if *state == self.finish { "doublecircle" } else { "circle" };
+            dot += &format!("    {} [label=\"{}\", shape={}];\n", state, label, shape);
+        }
+        dot += &format!("    start -> {};\n", self.start);
+        for state_f in &states {
+            let mut chars = self.move_table[state_f].keys().cloned().collect::<Vec<char>>();
+            chars.sort();
+            for c in chars {
+                let mut state_t_list = self.move_table[state_f][&c].iter().cloned().collect::<Vec<i32>>();
+                state_t_list.sort();
+                let label = match c {
+                    '@' => "ε".to_string(),
+                    _ => c.to_string().replace('\\', "\\\\").replace('"', "\\\"")
+                };
+                for state_t in state_t_list {
+                    dot += &format!("    {} -> {} [label=\"{}\"];\n", state_f, state_t, label);
+                }
+            }
+        }
+        dot += "}\n";
+        dot
+    }
+
+    /// # Mermaidの状態遷移図 (stateDiagram-v2) 形式の文字列に変換する
+    ///
+    /// ## note
+    /// - 状態nはノードsnとして出力し, 名前が付けられた状態はその名前を, それ以外は状態番号を表示名とする
+    /// - 開始状態は[*]からの遷移, 受理状態は[*]への遷移で表し, それぞれstart, finishクラスで装飾する
+    /// - ε遷移のラベルは"ε"とし, 英数字以外の文字はMermaidの実体参照 (#35;など) で出力する
+    ///
+    /// ## returns
+    /// String
+    pub fn to_mermaid(&self) -> String {
+        let mut states = self.move_table.keys().cloned().collect::<Vec<i32>>();
+        states.sort();
+        let escape = |c: char| match c {
+            '@' => "ε".to_string(),
+            _ if c.is_ascii_alphanumeric() || !c.is_ascii() => c.to_string(),
+            _ => format!("#{};", c as u32)
+        };
+
+        let mut mermaid = String::from("stateDiagram-v2\n");
+        mermaid += "    classDef start font-weight:bold\n";
+        mermaid += "    classDef finish stroke-width:3px\n";
+        for state in &states {
+            let label = match self.state_name(*state) {
+                Some(name) => name.chars().map(|c| if c == '"' { "#quot;".to_string() } else { c.to_string() }).collect(),
+                None => state.to_string()
+            };
+            mermaid += &format!("    state \"{}\" as s{}\n", label, state);
+        }
+        mermaid += &format!("    [*] --> s{}\n", self.start);
+        for state_f in &states {
+            let mut chars = self.move_table[state_f].keys().cloned().collect::<Vec<char>>();
+            chars.sort();
+            for c in chars {
+                let mut state_t_list = self.move_table[state_f][&c].iter().cloned().collect::<Vec<i32>>();
+                state_t_list.sort();
+                for state_t in state_t_list {
+                    mermaid += &format!("    s{} --> s{} : {}\n", state_f, state_t, escape(c));
+                }
+            }
+        }
+        mermaid += &format!("    s{} --> [*]\n", self.finish);
+        mermaid += &format!("    class s{} start\n", self.start);
+        mermaid += &format!("    class s{} finish\n", self.finish);
+        mermaid
+    }
+
+    /// # オートマトンのシミュレートを行う
     ///
     /// ## args
     /// - target: String => 対象文字列
@@ -149,137 +1271,1715 @@ impl NFA {
     /// ## returns
     /// - bool
     pub fn simulate(&self, target: String) -> bool {
-        // 状態管理用変数 宣言, 初期化
-        let mut old_states: HashSet<i32> = HashSet::new();
-        let mut new_states: HashSet<i32> = HashSet::new();
-        old_states.insert(self.start);
-        old_states.extend(self.epsilon_chain[&self.start].0.iter());
+        Self::simulate_state(self, &target).contains(&self.finish)
+    }
 
-        // シミュレート
-        let mut idx = 0;
-        let target = target.chars().collect::<Vec<char>>();
-        loop {
-            if idx == target.len() {
-                break;
-            }
-            let c = target[idx]; idx += 1;
-            for state in &old_states {
+    /// # 処理量に上限を設けてシミュレートを行う
+    ///
+    /// ## note
+    /// - 各文字で遷移元の状態を1つ処理するごとに1ステップと数える
+    /// - ステップ数がmax_stepsに達した時点で残りの入力を読まずに中断する
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    /// - max_steps: usize => 処理できる(状態, 文字位置)の組の数の上限
+    ///
+    /// ## returns
+    /// Result<bool, SimulationError>
+    pub fn simulate_bounded(&self, target: &str, max_steps: usize) -> Result<bool, SimulationError> {
+        let mut steps = 0;
+        let mut states = Self::initial_states(self);
+        for c in target.chars() {
+            let mut new_states: HashSet<i32> = HashSet::new();
+            for state in &states {
+                if steps == max_steps {
+                    return Err(SimulationError::StepLimitExceeded { steps_taken: steps });
+                }
+                steps += 1;
                 new_states.extend(&Self::get_closure(self, state, &c));
             }
-            new_states.extend(&Self::get_epsilon_closure(self, &old_states));
-            old_states.clear();
-            old_states.extend(new_states.iter());
-            new_states.clear();
+            let reachable_states = Self::get_epsilon_closure(self, &new_states);
+            new_states.extend(&reachable_states);
+            states = new_states;
         }
-        old_states.contains(&self.finish)
+        Ok(states.contains(&self.finish))
     }
 
-    /// # 状態Sからある文字Cを通じて到達できる状態を返す
-    fn get_closure(&self, state: &i32, c: &char) -> HashSet<i32> {
-        if Self::check_state(self, &state) {
-            if let Some(states) = self.move_table[&state].get(&c) {
-                return states.clone();
+    /// # シミュレートを行い, 受理されなかった場合はその理由を返す
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// RejectReason
+    pub fn simulate_explain(&self, target: &str) -> RejectReason {
+        let mut states = Self::initial_states(self);
+        for (position, character) in target.char_indices() {
+            states = Self::state_image(self, &states, character);
+            if states.is_empty() {
+                return RejectReason::DeadState { position, character };
             }
         }
-        HashSet::new()
+        if states.contains(&self.finish) {
+            return RejectReason::Accepted;
+        }
+        let mut active_states = states.into_iter().collect::<Vec<i32>>();
+        active_states.sort();
+        RejectReason::NoAcceptingState { active_states }
+    }
+
+    /// # シミュレートを行い, 最後の状態集合を返す
+    ///
+    /// ## note
+    /// 受理状態を含むかどうかに関わらず返すため, 呼び出し側で独自の受理条件を判定できる
+    ///
+    /// ## args
+    /// - text: &str => 対象文字列
+    ///
+    /// ## returns
+    /// HashSet<i32>
+    pub fn simulate_state(&self, text: &str) -> HashSet<i32> {
+        Self::states_after(self, text.chars())
+    }
+
+    /// # 複数の文字列のシミュレートを並列に行う
+    ///
+    /// ## note
+    /// - rayonフィーチャが必要
+    /// - シミュレート中にNFAは変更されないため, 各文字列を独立に処理できる
+    ///
+    /// ## args
+    /// - targets: &[&str] => 対象文字列の一覧
+    ///
+    /// ## returns
+    /// Vec<bool> => 各文字列が受理されたかどうか (入力と同じ順)
+    #[cfg(feature = "rayon")]
+    pub fn simulate_parallel(&self, targets: &[&str]) -> Vec<bool> {
+        targets.par_iter()
+            .map(|target| self.simulate_on_chars(target.chars()))
+            .collect()
+    }
+
+    /// # 複数の文字列のシミュレートを並列に行い, 受理された文字列の数を返す
+    ///
+    /// ## note
+    /// rayonフィーチャが必要
+    ///
+    /// ## args
+    /// - targets: &[&str] => 対象文字列の一覧
+    ///
+    /// ## returns
+    /// usize
+    #[cfg(feature = "rayon")]
+    pub fn simulate_parallel_count(&self, targets: &[&str]) -> usize {
+        targets.par_iter()
+            .filter(|target| self.simulate_on_chars(target.chars()))
+            .count()
+    }
+
+    /// # 文字のイテレータを1文字ずつ読み進めながらシミュレートを行う
+    ///
+    /// ## note
+    /// 入力全体を保持しないため, 非常に長い入力にも使える
+    ///
+    /// ## args
+    /// - input: I => 対象文字列の各文字を順に返すイテレータ
+    ///
+    /// ## returns
+    /// bool
+    pub fn simulate_on_chars<I: Iterator<Item = char>>(&self, input: I) -> bool {
+        Self::states_after(self, input).contains(&self.finish)
+    }
+
+    /// # 先頭からchar_offset文字を読み飛ばし, 残りの文字列に対してシミュレートを行う
+    ///
+    /// ## note
+    /// - 位置は文字単位 (バイト単位ではない)
+    /// - char_offsetが文字数以上の場合は, NFAが空文字列を受理するかどうかに関わらずfalse
+    ///
+    /// ## args
+    /// - text: &str => 対象文字列
+    /// - char_offset: usize => 読み飛ばす文字数
+    ///
+    /// ## returns
+    /// bool
+    pub fn simulate_from_char(&self, text: &str, char_offset: usize) -> bool {
+        if char_offset >= text.chars().count() {
+            return false;
+        }
+        Self::simulate_on_chars(self, text.chars().skip(char_offset))
+    }
+
+    /// # バイトのイテレータを1バイトずつ読み進めながらシミュレートを行う
+    ///
+    /// ## note
+    /// 各バイトをそのままcharに変換するため, ASCII文字のみを扱うNFA向け
+    ///
+    /// ## args
+    /// - input: I => 対象文字列の各バイトを順に返すイテレータ
+    ///
+    /// ## returns
+    /// bool
+    pub fn simulate_on_bytes<I: Iterator<Item = u8>>(&self, input: I) -> bool {
+        Self::simulate_on_chars(self, input.map(char::from))
+    }
+
+    /// # 逐次的なシミュレートを開始する
+    ///
+    /// ## returns
+    /// SimulationState
+    pub fn start_simulation(&self) -> SimulationState<'_> {
+        let mut simulation = SimulationState { nfa: self, states: HashSet::new() };
+        simulation.reset();
+        simulation
+    }
+
+    /// # 遷移が起こるたびにコールバックを呼び出しながらシミュレートを行う
+    ///
+    /// ## note
+    /// - 非決定的な遷移では, 実際に辿った遷移全てについてコールバックが呼ばれる
+    /// - ε遷移についてはコールバックは呼ばれない
+    ///
+    /// ## args
+    /// - text: &str => 対象文字列
+    /// - on_transition: F => コールバック (遷移元状態, 入力文字, 遷移先状態)
+    ///
+    /// ## returns
+    /// - bool
+    pub fn simulate_transition_events<F>(&self, text: &str, mut on_transition: F) -> bool
+    where
+        F: FnMut(i32, char, i32)
+    {
+        // 状態管理用変数 宣言, 初期化
+        let mut old_states: HashSet<i32> = HashSet::new();
+        let mut new_states: HashSet<i32> = HashSet::new();
+        old_states.insert(self.start);
+        old_states.extend(self.epsilon_chain[&self.start].0.iter());
+
+        // シミュレート
+        for c in text.chars() {
+            for state in &old_states {
+                for next_state in &Self::get_closure(self, state, &c) {
+                    on_transition(*state, c, *next_state);
+                    new_states.insert(*next_state);
+                }
+            }
+            let reachable_states = Self::get_epsilon_closure(self, &new_states);
+            new_states.extend(&reachable_states);
+            old_states.clear();
+            old_states.extend(new_states.iter());
+            new_states.clear();
+        }
+        old_states.contains(&self.finish)
+    }
+
+    /// # 対象文字列を受理する計算経路の数を数える
+    ///
+    /// ## note
+    /// - 文字による遷移の列が異なるものを別の経路として数える (ε遷移の辿り方の違いは区別しない)
+    /// - (入力位置, 状態) をキーにメモ化して計算する
+    /// - 経路数がusizeに収まらない場合はusize::MAXで飽和する
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// - usize
+    pub fn simulate_count(&self, target: &str) -> usize {
+        let target = target.chars().collect::<Vec<char>>();
+        let states = self.move_table.keys().cloned().collect::<Vec<i32>>();
+
+        // counts[s] := 入力位置idxで状態sに入った時, そこから受理に至る経路数
+        let mut counts: HashMap<i32, usize> = HashMap::new();
+        for state in &states {
+            let reachable = self.epsilon_chain[state].0.contains(&self.finish);
+            counts.insert(*state, (*state == self.finish || reachable) as usize);
+        }
+        for c in target.iter().rev() {
+            let mut new_counts: HashMap<i32, usize> = HashMap::new();
+            for state in &states {
+                let mut from_states = vec![*state];
+                from_states.extend(self.epsilon_chain[state].0.iter());
+                from_states.sort();
+                from_states.dedup();
+                let mut count: usize = 0;
+                for from_state in &from_states {
+                    for next_state in &Self::get_closure(self, from_state, c) {
+                        count = count.saturating_add(counts[next_state]);
+                    }
+                }
+                new_counts.insert(*state, count);
+            }
+            counts = new_counts;
+        }
+        counts[&self.start]
+    }
+
+    /// # ASCII文字の大文字小文字を区別せずにシミュレートする
+    ///
+    /// ## note
+    /// 入力文字そのものに加えて, その小文字と大文字でも遷移する
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// bool
+    pub fn simulate_case_insensitive(&self, target: &str) -> bool {
+        let mut states = Self::initial_states(self);
+        for c in target.chars() {
+            let mut new_states = Self::state_image(self, &states, c.to_ascii_lowercase());
+            new_states.extend(Self::state_image(self, &states, c.to_ascii_uppercase()));
+            states = new_states;
+        }
+        states.contains(&self.finish)
+    }
+
+    /// # 対象文字列をUnicode正規化してからシミュレートする
+    ///
+    /// ## note
+    /// 合成済み文字 ("é") と結合文字列 ("e\u{301}") のように表記が異なる入力を同じものとして扱う
+    ///
+    /// ## args
+    /// - text: &str => 対象文字列
+    /// - form: NormalizationForm => 正規化形式
+    ///
+    /// ## returns
+    /// bool
+    #[cfg(feature = "unicode-normalization")]
+    pub fn simulate_normalized_unicode(&self, text: &str, form: NormalizationForm) -> bool {
+        match form {
+            NormalizationForm::NFC => Self::simulate_on_chars(self, text.nfc()),
+            NormalizationForm::NFD => Self::simulate_on_chars(self, text.nfd()),
+            NormalizationForm::NFKC => Self::simulate_on_chars(self, text.nfkc()),
+            NormalizationForm::NFKD => Self::simulate_on_chars(self, text.nfkd())
+        }
+    }
+
+    /// # キャプチャグループの範囲と共にシミュレートする
+    ///
+    /// ## note
+    /// - capture_statesの各要素は(グループの入口の状態, グループの出口の状態)
+    /// - 状態ごとに1つのスレッドを持ち, 各スレッドはグループごとの範囲を記録する
+    ///   (入口に到達した位置で開始し, 出口に到達した位置で終了する)
+    /// - 同じ状態に複数のスレッドが到達した場合は先に到達したものを優先する
+    /// - 受理された場合に通過しなかったグループの範囲は(0, 0)となる. 位置はバイト単位
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    /// - capture_states: &[(i32, i32)] => キャプチャグループの一覧
+    ///
+    /// ## returns
+    /// Option<Vec<(usize, usize)>> => 各グループの(開始位置, 終了位置) (受理されなければNone)
+    pub fn simulate_with_captures(&self, target: &str, capture_states: &[(i32, i32)]) -> Option<Vec<(usize, usize)>> {
+        let mut threads: Vec<CaptureThread> = vec![];
+        let mut seen: HashSet<i32> = HashSet::new();
+        let captures = vec![None; capture_states.len()];
+        self.add_capture_thread(self.start, 0, captures, capture_states, &mut threads, &mut seen);
+        for (idx, c) in target.char_indices() {
+            let pos = idx + c.len_utf8();
+            let mut next_threads: Vec<CaptureThread> = vec![];
+            let mut next_seen: HashSet<i32> = HashSet::new();
+            for (state, captures) in &threads {
+                if let Some(state_t_list) = self.move_table[state].get(&c) {
+                    let mut state_t_list = state_t_list.iter().cloned().collect::<Vec<i32>>();
+                    state_t_list.sort();
+                    for state_t in state_t_list {
+                        self.add_capture_thread(state_t, pos, captures.clone(), capture_states, &mut next_threads, &mut next_seen);
+                    }
+                }
+            }
+            threads = next_threads;
+        }
+        threads.into_iter()
+            .find(|(state, _)| *state == self.finish)
+            .map(|(_, captures)| captures.into_iter().map(|span| span.unwrap_or((0, 0))).collect())
+    }
+
+    /// # 各文字を処理した後の状態集合の履歴と共にシミュレートする
+    ///
+    /// ## note
+    /// - デバッグ用途のため効率は重視していない
+    /// - 履歴のi番目はi+1文字目を処理した後 (ε閉包を含む) の状態を昇順に並べたもの
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// (bool, Vec<Vec<i32>>) => (受理されたかどうか, 状態集合の履歴)
+    pub fn simulate_with_trace(&self, target: &str) -> (bool, Vec<Vec<i32>>) {
+        let mut states = Self::initial_states(self);
+        let mut trace: Vec<Vec<i32>> = vec![];
+        for c in target.chars() {
+            states = Self::state_image(self, &states, c);
+            let mut sorted_states: Vec<i32> = states.iter().cloned().collect();
+            sorted_states.sort();
+            trace.push(sorted_states);
+        }
+        (states.contains(&self.finish), trace)
+    }
+
+    /// # シミュレート中の状態集合の大きさの最大値 (非決定性の度合い) を求める
+    ///
+    /// ## note
+    /// - 開始時と各文字を処理した後 (ε閉包を含む) の状態集合を対象とする
+    /// - 決定性のNFAでは1 (途中で状態集合が空になっても開始時の1となる)
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// usize
+    pub fn simulate_nondeterminism_degree(&self, target: &str) -> usize {
+        Self::state_set_sizes(self, target).into_iter().max().unwrap()
+    }
+
+    /// # シミュレート中の状態集合の大きさの平均を求める
+    ///
+    /// ## note
+    /// 開始時と各文字を処理した後 (ε閉包を含む) の, (文字数 + 1)個の状態集合の平均
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// f64
+    pub fn simulate_average_state_set_size(&self, target: &str) -> f64 {
+        let sizes = Self::state_set_sizes(self, target);
+        sizes.iter().sum::<usize>() as f64 / sizes.len() as f64
+    }
+
+    /// # 先頭に固定して, 対象文字列の接頭辞のうち最長の一致を探す
+    ///
+    /// ## note
+    /// - simulateと異なり, 文字列全体が一致する必要はない
+    /// - 長さはバイト単位
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// Option<usize> => 一致した接頭辞の長さ (一致しなければNone)
+    pub fn simulate_anchored_start(&self, target: &str) -> Option<usize> {
+        let mut states = Self::initial_states(self);
+        let mut longest = if states.contains(&self.finish) { Some(0) } else { None };
+        for (idx, c) in target.char_indices() {
+            states = Self::state_image(self, &states, c);
+            if states.is_empty() {
+                break;
+            }
+            if states.contains(&self.finish) {
+                longest = Some(idx + c.len_utf8());
+            }
+        }
+        longest
+    }
+
+    /// # 対象文字列の接頭辞のうち受理されるものの長さを全て求める
+    ///
+    /// ## note
+    /// - 空文字列も接頭辞に含む
+    /// - 長さはバイト単位で, 昇順に並ぶ
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// Vec<usize>
+    pub fn simulate_accepting_prefix_lengths(&self, target: &str) -> Vec<usize> {
+        let mut states = Self::initial_states(self);
+        let mut lengths = vec![];
+        if states.contains(&self.finish) {
+            lengths.push(0);
+        }
+        for (idx, c) in target.char_indices() {
+            states = Self::state_image(self, &states, c);
+            if states.is_empty() {
+                break;
+            }
+            if states.contains(&self.finish) {
+                lengths.push(idx + c.len_utf8());
+            }
+        }
+        lengths
+    }
+
+    /// # 対象文字列の接頭辞のうち受理されるものの数を数える
+    ///
+    /// ## note
+    /// 空文字列も接頭辞に含む
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// usize
+    pub fn simulate_count_accepting_prefixes(&self, target: &str) -> usize {
+        Self::simulate_accepting_prefix_lengths(self, target).len()
+    }
+
+    /// # 末尾に固定して, 対象文字列の接尾辞のうち最長の一致を探す
+    ///
+    /// ## note
+    /// - 反転したNFAで反転した文字列に対してsimulate_anchored_startを行う
+    /// - 反転したNFAは初回の呼び出し時に生成し, 遷移が変更されるまで使い回す
+    /// - 位置はバイト単位
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// Option<usize> => 一致した接尾辞の開始位置 (一致しなければNone)
+    pub fn simulate_anchored_end(&self, target: &str) -> Option<usize> {
+        let reversed_target: String = target.chars().rev().collect();
+        let rebuilt: NFA;
+        let mut reversed_nfa = self.reversed.get_or_init(|| self.reverse());
+        if reversed_nfa.start != self.finish || reversed_nfa.finish != self.start {
+            // 公開メンバのstart, finishが直接書き換えられていればキャッシュは使えない
+            rebuilt = self.reverse();
+            reversed_nfa = &rebuilt;
+        }
+        reversed_nfa
+            .simulate_anchored_start(&reversed_target)
+            .map(|len| target.len() - len)
+    }
+
+    /// # 対象文字列の接尾辞のうちいずれかが受理されるかを判定する (末尾に固定した一致)
+    ///
+    /// ## note
+    /// - 反転したNFAで反転した文字列をシミュレートする (simulate_anchored_endを利用)
+    /// - 空文字列も接尾辞に含む
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// bool
+    pub fn simulate_suffix(&self, target: &str) -> bool {
+        self.simulate_anchored_end(target).is_some()
+    }
+
+    /// # 対象文字列の部分文字列のうちいずれかが受理されるかを判定する
+    ///
+    /// ## note
+    /// 先頭から順に全ての開始位置でsimulate_anchored_startを試みる
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// bool
+    pub fn simulate_contains_pattern(&self, target: &str) -> bool {
+        target.char_indices()
+            .map(|(idx, _)| idx)
+            .chain(core::iter::once(target.len()))
+            .any(|idx| self.simulate_anchored_start(&target[idx..]).is_some())
+    }
+
+    /// # 対象文字列中の重なりのないマッチを全て返す
+    ///
+    /// ## note
+    /// - 先頭から順に各位置で最長一致を試み, マッチした場合はその終端から走査を再開する
+    /// - 長さ0のマッチの場合は無限ループを避けるため1文字進める
+    /// - 位置はバイト単位
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// Vec<(usize, usize)> => (開始位置, 終了位置)の一覧
+    pub fn simulate_all_matches(&self, target: &str) -> Vec<(usize, usize)> {
+        let mut matches: Vec<(usize, usize)> = vec![];
+        let mut idx = 0;
+        while idx <= target.len() {
+            let next_char_len = target[idx..].chars().next().map_or(1, |c| c.len_utf8());
+            match self.simulate_anchored_start(&target[idx..]) {
+                Some(len) => {
+                    matches.push((idx, idx + len));
+                    idx += if len == 0 { next_char_len } else { len };
+                }
+                None => idx += next_char_len
+            }
+        }
+        matches
+    }
+
+    /// # 対象文字列中で最も左にある最長の一致を探す
+    ///
+    /// ## args
+    /// - text: &str => 対象文字列
+    ///
+    /// ## returns
+    /// Option<Match> => 一致した部分 (一致しなければNone)
+    pub fn do_match<'t>(&self, text: &'t str) -> Option<Match<'t>> {
+        let boundaries = text.char_indices().map(|(idx, _)| idx).chain(Some(text.len()));
+        for start in boundaries {
+            if let Some(len) = self.simulate_anchored_start(&text[start..]) {
+                return Some(Match { text, start, end: start + len });
+            }
+        }
+        None
+    }
+
+    /// # 対象文字列中で最も左にある最長の一致を探す (POSIXの意味論)
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// Option<(usize, usize)> => (開始位置, 終了位置) (一致しなければNone)
+    pub fn simulate_leftmost_longest(&self, target: &str) -> Option<(usize, usize)> {
+        self.do_match(target).map(|m| (m.start(), m.end()))
+    }
+
+    /// # 対象文字列中で最も左にある最短の一致を探す
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// Option<(usize, usize)> => (開始位置, 終了位置) (一致しなければNone)
+    pub fn simulate_leftmost_shortest(&self, target: &str) -> Option<(usize, usize)> {
+        let boundaries = target.char_indices().map(|(idx, _)| idx).chain(Some(target.len()));
+        for start in boundaries {
+            let mut states = Self::initial_states(self);
+            if states.contains(&self.finish) {
+                return Some((start, start));
+            }
+            for (idx, c) in target[start..].char_indices() {
+                states = Self::state_image(self, &states, c);
+                if states.is_empty() {
+                    break;
+                }
+                if states.contains(&self.finish) {
+                    return Some((start, start + idx + c.len_utf8()));
+                }
+            }
+        }
+        None
+    }
+
+    /// # 対象文字列中の重なりのないマッチを全て返す
+    ///
+    /// ## note
+    /// simulate_all_matchesの別名
+    ///
+    /// ## args
+    /// - text: &str => 対象文字列
+    ///
+    /// ## returns
+    /// Vec<(usize, usize)> => (開始位置, 終了位置)の一覧
+    pub fn find_all_non_overlapping(&self, text: &str) -> Vec<(usize, usize)> {
+        self.simulate_all_matches(text)
+    }
+
+    /// # 長さmax_len以下の受理される文字列を全て列挙する
+    ///
+    /// ## note
+    /// - (状態集合, 残りの長さ)の組ごとに受理される接尾辞を求めてメモ化するため, 同じ状態集合に至る接頭辞が多くても探索は繰り返さない
+    /// - 状態集合が受理状態へ到達できる状態を含まなくなった枝は打ち切る
+    /// - 探索の深さはmax_len以下に制限されるため, 遷移が循環していても停止する
+    /// - 短い順 (同じ長さでは文字の昇順) に並べて返す
+    ///
+    /// ## args
+    /// - max_len: usize => 文字列の長さ (文字数) の上限
+    ///
+    /// ## returns
+    /// Vec<String>
+    pub fn accepting_words_up_to_length(&self, max_len: usize) -> Vec<String> {
+        let mut alphabet: Vec<char> = vec![];
+        for chars in self.move_table.values() {
+            alphabet.extend(chars.keys().filter(|c| **c != '@'));
+        }
+        alphabet.sort();
+        alphabet.dedup();
+        let productive_states = self.productive_states();
+
+        let mut memo: HashMap<(Vec<i32>, usize), Vec<String>> = HashMap::new();
+        let initial_states = Self::initial_states(self);
+        let mut words = Self::accepting_suffixes(self, &alphabet, &productive_states, &initial_states, max_len, &mut memo);
+        words.sort_by(|a, b| a.chars().count().cmp(&b.chars().count()).then_with(|| a.cmp(b)));
+        words
+    }
+
+    /// # 2つのNFAの両方に受理される文字列が存在するかどうかを判定する
+    ///
+    /// ## note
+    /// - 直積オートマトンを構築せず, (selfの状態, otherの状態)の組を開始状態から幅優先で探索する
+    /// - 両方の状態が共通して持つ文字による遷移のみを辿り, 遷移先はε閉包 (epsilon_chainに計算済み) まで広げる
+    /// - (self.finish, other.finish)に到達した時点で探索を打ち切る
+    ///
+    /// ## args
+    /// - other: &NFA => 比較対象のNFA
+    ///
+    /// ## returns
+    /// bool
+    pub fn intersect_language_nonempty(&self, other: &NFA) -> bool {
+        let closure = |nfa: &NFA, state: i32| {
+            let mut states = vec![state];
+            states.extend(nfa.epsilon_chain[&state].0.iter());
+            states
+        };
+        let mut visited: HashSet<(i32, i32)> = HashSet::new();
+        let mut queue: VecDeque<(i32, i32)> = VecDeque::new();
+        for state_a in closure(self, self.start) {
+            for state_b in closure(other, other.start) {
+                if visited.insert((state_a, state_b)) {
+                    queue.push_back((state_a, state_b));
+                }
+            }
+        }
+        while let Some((state_a, state_b)) = queue.pop_front() {
+            if state_a == self.finish && state_b == other.finish {
+                return true;
+            }
+            for (c, state_t_list_a) in &self.move_table[&state_a] {
+                if *c == '@' {
+                    continue;
+                }
+                let state_t_list_b = match other.move_table[&state_b].get(c) {
+                    Some(state_t_list_b) => state_t_list_b,
+                    None => continue
+                };
+                for state_t_a in state_t_list_a.iter().flat_map(|state| closure(self, *state)) {
+                    for state_t_b in state_t_list_b.iter().flat_map(|state| closure(other, *state)) {
+                        if visited.insert((state_t_a, state_t_b)) {
+                            queue.push_back((state_t_a, state_t_b));
+                        }
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// # 2つのNFAが同じ言語を受理するかどうかを判定する
+    ///
+    /// ## note
+    /// 両方のNFAを部分集合構成法で決定化しながら, 直積オートマトンの状態 (状態集合の組) を
+    /// 開始状態から幅優先で探索する. 片方だけが受理状態となる組に到達できれば, 両者の対称差が
+    /// 空でないため等価ではない. 状態番号は各NFAの中でのみ扱うため, 番号の空間が重なっていても構わない
+    ///
+    /// ## args
+    /// - other: &NFA => 比較対象のNFA
+    ///
+    /// ## returns
+    /// bool
+    pub fn equivalent(&self, other: &NFA) -> bool {
+        let mut alphabet: Vec<char> = vec![];
+        for nfa in &[self, other] {
+            for chars in nfa.move_table.values() {
+                alphabet.extend(chars.keys().filter(|c| **c != '@'));
+            }
+        }
+        alphabet.sort();
+        alphabet.dedup();
+
+        let sorted = |states: HashSet<i32>| {
+            let mut states = states.into_iter().collect::<Vec<i32>>();
+            states.sort();
+            states
+        };
+        let init = (sorted(self.initial_states()), sorted(other.initial_states()));
+        let mut visited: HashSet<(Vec<i32>, Vec<i32>)> = HashSet::new();
+        let mut queue: VecDeque<(Vec<i32>, Vec<i32>)> = VecDeque::new();
+        visited.insert(init.clone());
+        queue.push_back(init);
+        while let Some((states_a, states_b)) = queue.pop_front() {
+            if states_a.contains(&self.finish) != states_b.contains(&other.finish) {
+                return false;
+            }
+            let states_a_set = states_a.iter().cloned().collect::<HashSet<i32>>();
+            let states_b_set = states_b.iter().cloned().collect::<HashSet<i32>>();
+            for c in &alphabet {
+                let next = (
+                    sorted(self.state_image(&states_a_set, *c)),
+                    sorted(other.state_image(&states_b_set, *c))
+                );
+                if !visited.contains(&next) {
+                    visited.insert(next.clone());
+                    queue.push_back(next);
+                }
+            }
+        }
+        true
+    }
+
+    /// # 状態Sからある文字Cを通じて到達できる状態を返す
+    ///
+    /// ## note
+    /// - ε遷移は辿らない (遷移先のε閉包が必要な場合はget_epsilon_closureと組み合わせる)
+    /// - c = '@'を与えると, Sから直接ε遷移で移れる状態を返す
+    /// - 管理していない状態を与えた場合は空集合を返す
+    ///
+    /// ## args
+    /// - state: &i32 => 状態S
+    /// - c: &char => 文字C
+    ///
+    /// ## returns
+    /// HashSet<i32>
+    pub fn get_closure(&self, state: &i32, c: &char) -> HashSet<i32> {
+        if Self::check_state(self, state) {
+            if let Some(states) = self.move_table[state].get(c) {
+                return states.clone();
+            }
+        }
+        HashSet::new()
+    }
+
+    /// # 状態集合Sから文字Cで遷移し, さらにε遷移のみで到達可能な状態も加えた集合を返す
+    ///
+    /// ## note
+    /// - S内の各状態についてのget_closureの和集合に, get_epsilon_closureの結果を加えたもの
+    /// - 部分集合構成法やシミュレートの1ステップに相当する
+    ///
+    /// ## args
+    /// - states: &HashSet<i32> => 状態集合S
+    /// - c: char => 文字C
+    ///
+    /// ## returns
+    /// HashSet<i32>
+    pub fn state_image(&self, states: &HashSet<i32>, c: char) -> HashSet<i32> {
+        let mut new_states: HashSet<i32> = HashSet::new();
+        for state in states {
+            new_states.extend(&Self::get_closure(self, state, &c));
+        }
+        let reachable_states = Self::get_epsilon_closure(self, &new_states);
+        new_states.extend(&reachable_states);
+        new_states
+    }
+
+    /// # 状態集合Sからε-遷移のみで到達可能時な状態一覧を返す
+    ///
+    /// ## note
+    /// - 1回以上のε遷移で到達できる状態のみを返し, S自身の状態は含まない
+    /// - simulateなどはS自身を別途加えて使っている. 既存の呼び出し側がこの前提で書かれているため,
+    ///   自身を含める形には変更しない
+    /// - 管理していない状態は無視する
+    ///
+    /// ## args
+    /// - states: &HashSet<i32> => 状態集合S
+    ///
+    /// ## returns
+    /// HashSet<i32>
+    pub fn get_epsilon_closure(&self, states: &HashSet<i32>) -> HashSet<i32> {
+        let mut reachable_states: HashSet<i32> = HashSet::new();
+        for state in states {
+            if Self::check_state(self, state) {
+                reachable_states.extend(&self.epsilon_chain[state].0);
+            }
+        }
+        reachable_states
+    }
+
+    /// # 状態Sからε-遷移のみで到達可能な状態一覧を返す
+    ///
+    /// ## note
+    /// get_epsilon_closureと同様に, S自身は含まない
+    ///
+    /// ## args
+    /// - state: i32 => 状態S
+    ///
+    /// ## returns
+    /// HashSet<i32>
+    pub fn get_epsilon_closure_single(&self, state: i32) -> HashSet<i32> {
+        let mut states: HashSet<i32> = HashSet::new();
+        states.insert(state);
+        Self::get_epsilon_closure(self, &states)
+    }
+
+    /// # キャプチャ付きシミュレートのスレッドを追加し, ε遷移を1つずつ辿ってスレッドを広げる
+    fn add_capture_thread(
+        &self,
+        state: i32,
+        pos: usize,
+        mut captures: Vec<Option<(usize, usize)>>,
+        capture_states: &[(i32, i32)],
+        threads: &mut Vec<CaptureThread>,
+        seen: &mut HashSet<i32>
+    ) {
+        if !seen.insert(state) {
+            return;
+        }
+        for (idx, (enter_state, exit_state)) in capture_states.iter().enumerate() {
+            if state == *enter_state {
+                captures[idx] = Some((pos, pos));
+            }
+            if state == *exit_state {
+                if let Some((start, _)) = captures[idx] {
+                    captures[idx] = Some((start, pos));
+                }
+            }
+        }
+        threads.push((state, captures.clone()));
+        if let Some(state_t_list) = self.move_table[&state].get(&'@') {
+            let mut state_t_list = state_t_list.iter().cloned().collect::<Vec<i32>>();
+            state_t_list.sort();
+            for state_t in state_t_list {
+                self.add_capture_thread(state_t, pos, captures.clone(), capture_states, threads, seen);
+            }
+        }
+    }
+
+    /// # state_aからstate_bへの文字cによる遷移が既に存在するかどうか
+    fn has_chain(&self, state_a: i32, state_b: i32, c: char) -> bool {
+        self.move_table.get(&state_a)
+            .and_then(|chars| chars.get(&c))
+            .is_some_and(|state_t_list| state_t_list.contains(&state_b))
+    }
+
+    /// # ε-chain更新処理
+    ///
+    /// ## note
+    /// state_aへε遷移で到達できる全ての状態 (state_a自身を含む) を幅優先で辿り,
+    /// それぞれの前方集合にstate_bとその前方集合を加える. 訪問済みの状態は辿らないため,
+    /// ε遷移が循環していても停止する
+    fn update_epsilon_chain(&mut self, state_a: &i32, state_b: &i32) {
+        self.epsilon_chain.get_mut(state_b).unwrap().1.insert(*state_a);
+        let mut f_states: HashSet<i32> = HashSet::new();
+        f_states.insert(*state_b);
+        f_states.extend(self.epsilon_chain[state_b].0.iter());
+
+        let mut visited: HashSet<i32> = HashSet::new();
+        let mut b_state_queue: VecDeque<i32> = VecDeque::new();
+        visited.insert(*state_a);
+        b_state_queue.push_back(*state_a);
+        while let Some(state) = b_state_queue.pop_front() {
+            self.epsilon_chain.get_mut(&state).unwrap().0.extend(&f_states);
+            for b_state in &self.epsilon_chain[&state].1 {
+                if visited.insert(*b_state) {
+                    b_state_queue.push_back(*b_state);
+                }
+            }
+        }
+    }
+
+    /// # 開始状態とそこからε遷移のみで到達可能な状態の集合を返す
+    fn initial_states(&self) -> HashSet<i32> {
+        let mut states: HashSet<i32> = HashSet::new();
+        states.insert(self.start);
+        states.extend(self.epsilon_chain[&self.start].0.iter());
+        states
+    }
+
+    /// # 開始時と各文字を処理した後の状態集合の大きさを順に返す
+    fn state_set_sizes(&self, target: &str) -> Vec<usize> {
+        let mut states = Self::initial_states(self);
+        let mut sizes = vec![states.len()];
+        for c in target.chars() {
+            states = Self::state_image(self, &states, c);
+            sizes.push(states.len());
+        }
+        sizes
+    }
+
+    /// # 開始状態から入力の各文字で遷移した後の状態集合を返す
+    fn states_after<I: Iterator<Item = char>>(&self, input: I) -> HashSet<i32> {
+        let mut states = Self::initial_states(self);
+        for c in input {
+            states = Self::state_image(self, &states, c);
+        }
+        states
+    }
+
+    /// # 状態集合statesから長さremaining以下で受理される接尾辞を全て返す (memoに(状態集合, 残りの長さ)ごとの結果を保持する)
+    fn accepting_suffixes(
+        &self,
+        alphabet: &[char],
+        productive_states: &HashSet<i32>,
+        states: &HashSet<i32>,
+        remaining: usize,
+        memo: &mut HashMap<(Vec<i32>, usize), Vec<String>>
+    ) -> Vec<String> {
+        let mut key: Vec<i32> = states.iter().cloned().collect();
+        key.sort();
+        let key = (key, remaining);
+        if let Some(suffixes) = memo.get(&key) {
+            return suffixes.clone();
+        }
+        let mut suffixes: Vec<String> = vec![];
+        if states.iter().any(|state| productive_states.contains(state)) {
+            if states.contains(&self.finish) {
+                suffixes.push(String::new());
+            }
+            if remaining > 0 {
+                for c in alphabet {
+                    let next_states = Self::state_image(self, states, *c);
+                    if next_states.is_empty() {
+                        continue;
+                    }
+                    for suffix in Self::accepting_suffixes(self, alphabet, productive_states, &next_states, remaining - 1, memo) {
+                        suffixes.push(format!("{}{}", c, suffix));
+                    }
+                }
+            }
+        }
+        memo.insert(key, suffixes.clone());
+        suffixes
+    }
+
+    /// # [state_f state_t]の状態を管理対象に追加する
+    fn reserve_range(&mut self, state_f: i32, state_t: i32) -> Result<(), NFAError> {
+        self.reversed.clear();
+        for state in state_f..=state_t {
+            if state < 0 || state >= NODE_LIMIT as i32 {
+                return Err(NFAError::StateIndexOutOfRange { state });
+            }
+            if Self::check_state(self, &state) {
+                return Err(NFAError::AlreadyReservedState);
+            }
+            if self.reserved_state.len() <= state as usize {
+                self.reserved_state.resize(state as usize + 1, false);
+            }
+            self.reserved_state[state as usize] = true;
+            self.move_table.insert(state, HashMap::new());
+            self.epsilon_chain.insert(state, (HashSet::new(), HashSet::new())); // (forward, back)
+        }
+        Ok(())
+    }
+
+    /// # otherの状態と遷移を取り込む (開始状態, 受理状態はselfのもののまま)
+    fn absorb(&mut self, other: NFA) -> Result<(), NFAError> {
+        self.reversed.clear();
+        for state in other.move_table.keys() {
+            self.reserve_range(*state, *state)?;
+        }
+        for (state_f, chars) in other.move_table.iter() {
+            for (c, state_t_list) in chars.iter() {
+                for state_t in state_t_list {
+                    self.set_chain(*state_f, *state_t, *c)?;
+                }
+            }
+        }
+        self.state_names.extend(other.state_names);
+        Ok(())
+    }
+
+    /// # 管理している状態のうち最小のものを返す
+    fn min_state(&self) -> i32 {
+        *self.move_table.keys().min().unwrap()
+    }
+
+    /// # 管理している状態のうち最大のものを返す
+    fn max_state(&self) -> i32 {
+        *self.move_table.keys().max().unwrap()
+    }
+
+    /// # 自分が管理する状態かどうかチェック
+    fn check_state(&self, state: &i32) -> bool {
+        if 0 <= *state && (*state as usize) < self.reserved_state.len() {
+            return self.reserved_state[*state as usize];
+        }
+        false
+    }
+}
+
+impl SimulationState<'_> {
+    /// # 1文字与えて状態を更新する
+    ///
+    /// ## args
+    /// - c: char => 入力文字
+    ///
+    /// ## returns
+    /// &mut SimulationState
+    pub fn feed(&mut self, c: char) -> &mut Self {
+        self.states = self.nfa.state_image(&self.states, c);
+        self
+    }
+
+    /// # 文字列を先頭から1文字ずつ与えて状態を更新する
+    ///
+    /// ## args
+    /// - s: &str => 入力文字列
+    ///
+    /// ## returns
+    /// &mut SimulationState
+    pub fn feed_str(&mut self, s: &str) -> &mut Self {
+        for c in s.chars() {
+            self.feed(c);
+        }
+        self
+    }
+
+    /// # これまでに与えた文字列が受理されるかどうかを返す
+    pub fn is_accepting(&self) -> bool {
+        self.states.contains(&self.nfa.finish)
+    }
+
+    /// # 何も与えていない状態に戻す
+    pub fn reset(&mut self) {
+        self.states = self.nfa.initial_states();
+    }
+}
+
+impl ProductAccept {
+    /// # a, bそれぞれが受理するかどうかから, 直積オートマトンが受理するかどうかを返す
+    fn accepts(&self, accepted_a: bool, accepted_b: bool) -> bool {
+        match self {
+            ProductAccept::Both => accepted_a && accepted_b,
+            ProductAccept::AOnly => accepted_a && !accepted_b,
+            ProductAccept::BOnly => !accepted_a && accepted_b,
+            ProductAccept::Either => accepted_a || accepted_b,
+            ProductAccept::ExactlyOne => accepted_a != accepted_b
+        }
+    }
+}
+
+impl<'t> Match<'t> {
+    /// # 一致した部分の開始位置
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// # 一致した部分の終了位置
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// # 一致した部分の文字列
+    pub fn as_str(&self) -> &'t str {
+        &self.text[self.start..self.end]
+    }
+
+    /// # 一致した部分の長さ (バイト単位)
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// # 一致した部分が空文字列かどうか
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// # 遷移表を表形式で書き出す
+///
+/// ## note
+/// - "State | Character | Successors"の表で, 1行が(状態, 文字)ごとの遷移先一覧に対応する
+/// - 行は状態, 文字の昇順に並ぶ. 遷移を持たない状態は文字と遷移先を"-"とした行になる
+/// - 開始状態には'→', 受理状態には'*'を付け, ε遷移の文字は'ε'と表す
+impl fmt::Display for NFA {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut states: Vec<&i32> = self.move_table.keys().collect();
+        states.sort();
+
+        let mut rows: Vec<(String, String, String)> = vec![];
+        for state in states {
+            let mut label = String::new();
+            if *state == self.start {
+                label.push('→');
+            }
+            if *state == self.finish {
+                label.push('*');
+            }
+            write!(label, "{}", state)?;
+
+            let mut chars: Vec<(&char, &HashSet<i32>)> = self.move_table[state].iter()
+                .filter(|(_, state_t_list)| !state_t_list.is_empty())
+                .collect();
+            chars.sort_by_key(|(c, _)| **c);
+            if chars.is_empty() {
+                rows.push((label, "-".to_string(), "-".to_string()));
+                continue;
+            }
+            for (c, state_t_list) in chars {
+                let mut state_t_list: Vec<&i32> = state_t_list.iter().collect();
+                state_t_list.sort();
+                let mut successors = String::new();
+                for (idx, state_t) in state_t_list.iter().enumerate() {
+                    if idx > 0 {
+                        successors.push_str(", ");
+                    }
+                    write!(successors, "{}", state_t)?;
+                }
+                let c = if *c == '@' { 'ε' } else { *c };
+                rows.push((label.clone(), c.to_string(), successors));
+            }
+        }
+
+        let width = |header: &str, column: &dyn Fn(&(String, String, String)) -> usize| {
+            rows.iter().map(column).chain(Some(header.chars().count())).max().unwrap()
+        };
+        let state_width = width("State", &|row| row.0.chars().count());
+        let char_width = width("Character", &|row| row.1.chars().count());
+        writeln!(f, "{:<w1$} | {:<w2$} | Successors", "State", "Character", w1 = state_width, w2 = char_width)?;
+        for (label, c, successors) in &rows {
+            writeln!(f, "{:<w1$} | {:<w2$} | {}", label, c, successors, w1 = state_width, w2 = char_width)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for NFAError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NFAError::NonReservedState => write!(f, "state is not reserved"),
+            NFAError::AlreadyReservedState => write!(f, "state is already reserved"),
+            NFAError::StateIndexOutOfRange { state } => write!(f, "state {} is out of range (0..{})", state, NODE_LIMIT),
+            NFAError::DuplicateChain { from, to, c } => write!(f, "transition {} -({})-> {} already exists", from, c, to)
+        }
+    }
+}
+
+impl ReversedCache {
+    /// # キャッシュした反転NFAを返す (未生成であればbuildで生成する)
+    fn get_or_init<F: FnOnce() -> NFA>(&self, build: F) -> &NFA {
+        self.0.get_or_init(|| Box::new(build()))
+    }
+
+    /// # キャッシュを破棄する
+    fn clear(&mut self) {
+        self.0.take();
+    }
+}
+
+impl fmt::Debug for ReversedCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ReversedCache {{ cached: {} }}", self.0.get().is_some())
+    }
+}
+
+impl PartialEq for ReversedCache {
+    fn eq(&self, _: &ReversedCache) -> bool {
+        true
+    }
+}
+
+/// # (a|b)* abbを受理するテスト用のNFA
+#[cfg(test)]
+#[allow(unused_must_use)]
+pub(crate) fn abb_nfa() -> NFA {
+    let mut nfa = NFA::new(0, 10).ok().unwrap();
+    nfa.set_chain(0, 7, '@');
+    nfa.set_chain(0, 1, '@');
+    nfa.set_chain(1, 2, '@');
+    nfa.set_chain(1, 4, '@');
+    nfa.set_chain(2, 3, 'a');
+    nfa.set_chain(3, 6, '@');
+    nfa.set_chain(4, 5, 'b');
+    nfa.set_chain(5, 6, '@');
+    nfa.set_chain(6, 1, '@');
+    nfa.set_chain(6, 7, '@');
+    nfa.set_chain(7, 8, 'a');
+    nfa.set_chain(8, 9, 'b');
+    nfa.set_chain(9, 10, 'b');
+    nfa
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ Cursor, Read };
+    use rand::seq::SliceRandom;
+    use super::{ abb_nfa, HashMap, HashSet, NFA, NFAError, NODE_LIMIT, ProductAccept, RejectReason, SimulationError };
+
+    #[test]
+    fn test_init() {
+        let nfa = NFA::new(0, 4).ok().unwrap();
+        assert_eq!(nfa.start, 0);
+        assert_eq!(nfa.finish, 4);
+    }
+
+    #[test]
+    fn test_strict_mode() {
+        let mut nfa = NFA::new(0, 2).ok().unwrap();
+        assert_eq!(nfa.set_chain(0, 1, 'a'), Ok(()));
+        assert_eq!(nfa.set_chain(0, 1, 'a'), Ok(()));
+
+        let mut nfa = NFA::new(0, 2).ok().unwrap().with_strict_mode();
+        assert_eq!(nfa.set_chain(0, 1, 'a'), Ok(()));
+        assert_eq!(nfa.set_chain(0, 1, 'a'), Err(NFAError::DuplicateChain { from: 0, to: 1, c: 'a' }));
+        assert_eq!(nfa.set_chain(0, 1, 'b'), Ok(()));
+        assert_eq!(nfa.set_chain(1, 2, '@'), Ok(()));
+        assert_eq!(nfa.set_chain(1, 2, '@'), Err(NFAError::DuplicateChain { from: 1, to: 2, c: '@' }));
+        assert!(nfa.build_case_insensitive().is_ok());
+        assert!(nfa.simulate("B".to_string()));
+    }
+
+    #[test]
+    fn test_display() {
+        let nfa = abb_nfa();
+        let table = format!("{}", nfa);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "State | Character | Successors");
+        assert_eq!(lines[1], "→0    | ε         | 1, 7");
+        assert_eq!(lines[3], "2     | a         | 3");
+        assert_eq!(lines[lines.len() - 1], "*10   | -         | -");
+        assert_eq!(lines.len(), 12);
+
+        assert_eq!(NFAError::StateIndexOutOfRange { state: 1200 }.to_string(), "state 1200 is out of range (0..1000)");
+        assert_eq!(NFAError::DuplicateChain { from: 0, to: 1, c: 'a' }.to_string(), "transition 0 -(a)-> 1 already exists");
+    }
+
+    #[test]
+    fn test_init_out_of_range() {
+        assert_eq!(NFA::new(-1, 5).err(), Some(NFAError::StateIndexOutOfRange { state: -1 }));
+        assert_eq!(NFA::new(998, 1000).err(), Some(NFAError::StateIndexOutOfRange { state: 1000 }));
+        assert!(NFA::new(998, 999).is_ok());
+        let nfa = NFA::new(0, 1).ok().unwrap();
+        assert_eq!(NFA::reserve(nfa, 5, 1200).err(), Some(NFAError::StateIndexOutOfRange { state: 1000 }));
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_reserve_states() {
+        let nfa = NFA::new(0, 0).ok().unwrap();
+        let mut nfa = NFA::reserve_states(nfa, &[2, 5, 10]).ok().unwrap();     // ab*cを受理するNFA (状態0, 2, 5, 10)
+        nfa.finish = 10;
+        nfa.set_chain(0, 2, 'a');
+        nfa.set_chain(2, 2, 'b');
+        nfa.set_chain(2, 5, 'c');
+        nfa.set_chain(5, 10, '@');
+        assert_eq!(nfa.set_chain(0, 1, 'x'), Err(NFAError::NonReservedState));
+        assert!(nfa.simulate("ac".to_string()));
+        assert!(nfa.simulate("abbbc".to_string()));
+        assert!(!nfa.simulate("ab".to_string()));
+        assert!(!nfa.simulate("bc".to_string()));
+
+        assert_eq!(NFA::reserve_states(nfa.clone(), &[3, 5]).err(), Some(NFAError::AlreadyReservedState));
+        assert_eq!(NFA::reserve_states(nfa.clone(), &[3, 3]).err(), Some(NFAError::AlreadyReservedState));
+        assert_eq!(NFA::reserve_states(nfa.clone(), &[3, -1]).err(), Some(NFAError::StateIndexOutOfRange { state: -1 }));
+        assert_eq!(NFA::reserve_state(nfa.clone(), 10).err(), Some(NFAError::AlreadyReservedState));
+        let nfa = NFA::reserve_state(nfa, 1).ok().unwrap();
+        assert!(NFA::reserve_states(nfa, &[]).is_ok());
+    }
+
+    #[test]
+    #[allow(unused_must_use, clippy::useless_vec)]
+    fn test_merge_expand_nfa() {
+        let testcases = vec![
+            ((0, 10), (11, 20)),        // #1
+            ((0, 4), (2, 10)),          // #2
+        ];
+        let expect_results = vec![true, false];
+        for (testcase, result) in testcases.iter().zip(expect_results.iter()) {
+            let nfa_a = NFA::new((testcase.0).0, (testcase.0).1).ok().unwrap();
+            let nfa_b = NFA::new((testcase.1).0, (testcase.1).1).ok().unwrap();
+            assert_eq!(*result, NFA::merge(nfa_a, nfa_b, 0, 1).is_ok());
+        }
+    }
+
+    #[test]
+    #[allow(unused_must_use, clippy::bool_assert_comparison)]
+    fn test_merge_chain_process() {
+        let mut nfa_a = NFA::new(1, 6).ok().unwrap();     // (a|b)*
+        nfa_a.set_chain(1, 2, '@');
+        nfa_a.set_chain(1, 4, '@');
+        nfa_a.set_chain(2, 3, 'a');
+        nfa_a.set_chain(4, 5, 'b');
+        nfa_a.set_chain(3, 6, '@');
+        nfa_a.set_chain(5, 6, '@');
+        nfa_a.set_chain(6, 1, '@');
+
+        let mut nfa_b = NFA::new(7, 10).ok().unwrap();     // aa|bb
+        nfa_b.set_chain(7, 8, 'a');
+        nfa_b.set_chain(8, 10, 'a');
+        nfa_b.set_chain(7, 9, 'b');
+        nfa_b.set_chain(9, 10, 'b');
+
+        let merged_nfa = NFA::merge(nfa_a, nfa_b, 6, 7).ok().unwrap();
+        assert_eq!(merged_nfa.simulate("aaaaaa".to_string()), true);
+        assert_eq!(merged_nfa.simulate("aaaabb".to_string()), true);
+        assert_eq!(merged_nfa.simulate("abababaab".to_string()), false);
+        assert_eq!(merged_nfa.simulate("abababaabba".to_string()), false);
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_merge_copies_transitions() {
+        let mut nfa_a = NFA::new(0, 1).ok().unwrap();     // x
+        nfa_a.set_chain(0, 1, 'x');
+
+        let mut nfa_b = NFA::new(2, 5).ok().unwrap();     // y(z|@)
+        nfa_b.set_chain(2, 3, 'y');
+        nfa_b.set_chain(3, 4, 'z');
+        nfa_b.set_chain(3, 5, '@');
+        nfa_b.set_chain(4, 5, '@');
+
+        let merged_nfa = NFA::merge(nfa_a, nfa_b.clone(), 1, 2).ok().unwrap();
+        assert!(merged_nfa.simulate("xy".to_string()));
+        assert!(merged_nfa.simulate("xyz".to_string()));
+        assert!(!merged_nfa.simulate("x".to_string()));
+        assert!(!merged_nfa.simulate("xz".to_string()));
+        for chain in nfa_b.to_transition_table() {
+            assert!(merged_nfa.to_transition_table().contains(&chain));
+        }
+    }
+
+    #[test]
+    fn test_transition_table() {
+        let nfa = abb_nfa();
+
+        let table = nfa.to_transition_table();
+        assert_eq!(table.len(), 13);
+        assert_eq!(table[0], (0, 1, '@'));
+        assert_eq!(table[12], (9, 10, 'b'));
+
+        let restored = NFA::from_transition_table(nfa.start, nfa.finish, &table).ok().unwrap();
+        assert_eq!(restored.to_transition_table(), table);
+        assert_eq!(restored, nfa);
+        for target in &["a", "abb", "aba", "abbbabb", "bbbbbbaaabb", "aaaaaaaaaaaaaaaaaaab"] {
+            assert_eq!(restored.simulate(target.to_string()), nfa.simulate(target.to_string()));
+        }
+
+        let nfa = NFA::from_transition_table(3, 20, &[(3, 20, 'x')]).ok().unwrap();
+        assert!(nfa.simulate("x".to_string()));
+        assert_eq!(nfa.reserved_state.len(), 21);
+        assert_eq!(
+            NFA::from_transition_table(0, 1, &[(0, 1, 'a'), (1, 1000, 'b')]).err(),
+            Some(NFAError::StateIndexOutOfRange { state: 1000 })
+        );
+        assert_eq!(
+            NFA::from_transition_table(-2, 1, &[]).err(),
+            Some(NFAError::StateIndexOutOfRange { state: -2 })
+        );
+    }
+
+    #[test]
+    fn test_for_char_set() {
+        let nfa = NFA::for_char_set(&['a'..='c', 'x'..='x', '@'..='@']);
+        assert!(nfa.simulate("a".to_string()));
+        assert!(nfa.simulate("c".to_string()));
+        assert!(nfa.simulate("x".to_string()));
+        assert!(!nfa.simulate("d".to_string()));
+        assert!(!nfa.simulate("@".to_string()));
+        assert!(!nfa.simulate("ab".to_string()));
+        assert!(!nfa.simulate("".to_string()));
+    }
+
+    #[test]
+    fn test_for_literal() {
+        let nfa = NFA::for_literal("abc").ok().unwrap();
+        assert!(nfa.simulate("abc".to_string()));
+        assert!(!nfa.simulate("ab".to_string()));
+        assert!(!nfa.simulate("abcc".to_string()));
+        assert!(NFA::for_literal("").ok().unwrap().simulate("".to_string()));
+    }
+
+    #[test]
+    fn test_from_literal_set() {
+        let words: Vec<String> = (0..50).map(|n| format!("keyword{}", n)).collect();
+        let words: Vec<&str> = words.iter().map(|word| word.as_str()).collect();
+        let trie = NFA::from_literal_set(0, 1, &words).ok().unwrap();
+        let naive = NFA::for_keyword_set(&words).ok().unwrap();
+        assert!(trie.state_count() * 2 < naive.state_count());
+        assert_eq!(trie.state_count(), 7 + 10 + 40 + 2);
+        for word in &words {
+            assert!(trie.simulate(word.to_string()));
+        }
+        assert!(!trie.simulate("keyword".to_string()));
+        assert!(!trie.simulate("keyword50".to_string()));
+        assert!(!trie.simulate("keyword100".to_string()));
+        assert!(trie.equivalent(&naive));
+
+        let trie = NFA::from_literal_set(3, 0, &["", "a", "ab", "ab"]).ok().unwrap();
+        assert_eq!((trie.start, trie.finish, trie.state_count()), (3, 0, 4));
+        assert!(trie.simulate("".to_string()));
+        assert!(trie.simulate("ab".to_string()));
+        assert!(!trie.simulate("b".to_string()));
+    }
+
+    #[test]
+    fn test_for_keyword_set() {
+        let nfa = NFA::for_keyword_set(&["if", "in", "else", "x"]).ok().unwrap();
+        assert!(nfa.simulate("if".to_string()));
+        assert!(nfa.simulate("in".to_string()));
+        assert!(nfa.simulate("else".to_string()));
+        assert!(nfa.simulate("x".to_string()));
+        assert!(!nfa.simulate("i".to_string()));
+        assert!(!nfa.simulate("ifx".to_string()));
+        assert!(!nfa.simulate("".to_string()));
+        assert!(NFA::for_keyword_set(&["", "a"]).ok().unwrap().simulate("".to_string()));
+        assert!(!NFA::for_keyword_set(&[]).ok().unwrap().simulate("".to_string()));
+    }
+
+    #[test]
+    fn test_concat_and_kleene_star() {
+        let nfa_a = NFA::kleene_star(NFA::for_literal("ab").ok().unwrap()).ok().unwrap();      // (ab)*c
+        let nfa = NFA::concat(nfa_a, NFA::for_char_set(&['c'..='c'])).ok().unwrap();
+        assert!(nfa.simulate("c".to_string()));
+        assert!(nfa.simulate("abc".to_string()));
+        assert!(nfa.simulate("abababc".to_string()));
+        assert!(!nfa.simulate("ab".to_string()));
+        assert!(!nfa.simulate("abac".to_string()));
+        assert!(!nfa.simulate("cc".to_string()));
+    }
+
+    #[test]
+    fn test_simulate_accepting_prefixes() {
+        let nfa = NFA::kleene_star(NFA::for_literal("a").ok().unwrap()).ok().unwrap();     // a*
+        assert_eq!(nfa.simulate_accepting_prefix_lengths("aaa"), vec![0, 1, 2, 3]);
+        assert_eq!(nfa.simulate_count_accepting_prefixes("aaa"), 4);
+        assert_eq!(nfa.simulate_accepting_prefix_lengths("aab"), vec![0, 1, 2]);
+        assert_eq!(nfa.simulate_count_accepting_prefixes(""), 1);
+
+        let nfa = NFA::for_keyword_set(&["if", "ifdef", "あ"]).ok().unwrap();
+        assert_eq!(nfa.simulate_accepting_prefix_lengths("ifdefs"), vec![2, 5]);
+        assert_eq!(nfa.simulate_accepting_prefix_lengths("あい"), vec![3]);
+        assert_eq!(nfa.simulate_count_accepting_prefixes("i"), 0);
+    }
+
+    #[test]
+    fn test_one_or_more() {
+        let a_nfa = NFA::for_literal("a").ok().unwrap();
+        let nfa = NFA::one_or_more(a_nfa.clone()).ok().unwrap();
+        assert!(nfa.simulate("a".to_string()));
+        assert!(nfa.simulate("aaa".to_string()));
+        assert!(!nfa.simulate("".to_string()));
+        assert!(!nfa.simulate("ab".to_string()));
+
+        let nfa = NFA::optional(a_nfa).ok().unwrap();
+        assert!(nfa.simulate("".to_string()));
+        assert!(nfa.simulate("a".to_string()));
+        assert!(!nfa.simulate("aa".to_string()));
+    }
+
+    #[test]
+    fn test_for_colon_list() {
+        let dir = NFA::one_or_more(NFA::for_char_set(&['a'..='z', '/'..='/'])).ok().unwrap();        // [a-z/]+
+        let nfa = NFA::for_colon_list(dir).ok().unwrap();
+        assert!(nfa.simulate("/usr/bin".to_string()));
+        assert!(nfa.simulate("/usr/local/bin:/usr/bin:/bin".to_string()));
+        assert!(!nfa.simulate("".to_string()));
+        assert!(!nfa.simulate("/usr/bin:".to_string()));
+        assert!(!nfa.simulate(":/usr/bin".to_string()));
+        assert!(!nfa.simulate("/usr/bin::/bin".to_string()));
+
+        let nfa = NFA::for_colon_list(NFA::for_literal("").ok().unwrap()).ok().unwrap();         // 空の項目
+        assert!(nfa.simulate("".to_string()));
+        assert!(nfa.simulate("::".to_string()));
+        assert!(!nfa.simulate("a".to_string()));
     }
 
-    /// # 状態集合Sからε-遷移のみで到達可能時な状態一覧を返す
-    fn get_epsilon_closure(&self, states: &HashSet<i32>) -> HashSet<i32> {
-        let mut reachable_states: HashSet<i32> = HashSet::new();
-        for state in states {
-            if Self::check_state(self, state) {
-                reachable_states.extend(&self.epsilon_chain[state].0);
-            }
-        }
-        reachable_states
+    #[test]
+    fn test_for_repeat() {
+        let mut base = NFA::new(0, 1).ok().unwrap();      // a*b
+        base.set_chain(0, 0, 'a').ok().unwrap();
+        base.set_chain(0, 1, 'b').ok().unwrap();
+
+        let nfa = NFA::for_repeat(&base, 2).ok().unwrap();
+        assert!(nfa.simulate("bb".to_string()));
+        assert!(nfa.simulate("abaab".to_string()));
+        assert!(!nfa.simulate("b".to_string()));
+        assert!(!nfa.simulate("bbb".to_string()));
+        assert!(NFA::for_repeat(&base, 0).ok().unwrap().simulate("".to_string()));
+
+        let nfa = NFA::for_range_repeat(&base, 0, 2).ok().unwrap();
+        assert!(nfa.simulate("".to_string()));
+        assert!(nfa.simulate("ab".to_string()));
+        assert!(nfa.simulate("bab".to_string()));
+        assert!(!nfa.simulate("a".to_string()));
+        assert!(!nfa.simulate("bba".to_string()));
+        assert!(!nfa.simulate("bbb".to_string()));
+
+        let nfa = NFA::for_range_repeat(&NFA::for_char_set(&['x'..='x']), 2, 4).ok().unwrap();
+        assert!(!nfa.simulate("x".to_string()));
+        assert!(nfa.simulate("xx".to_string()));
+        assert!(nfa.simulate("xxxx".to_string()));
+        assert!(!nfa.simulate("xxxxx".to_string()));
+        assert!(!NFA::for_range_repeat(&base, 3, 2).ok().unwrap().simulate("bb".to_string()));
+        assert!(NFA::for_repeat(&base, 600).is_err());
     }
 
-    /// # ε-chain更新処理
-    fn update_epsilon_chain(&mut self, state_a: &i32, state_b: &i32) {
-        self.epsilon_chain.get_mut(&state_a).unwrap().0.insert(*state_b);
-        self.epsilon_chain.get_mut(&state_b).unwrap().1.insert(*state_a);
-        let mut b_state_stack: Vec<i32> = vec![*state_a];
-        let mut f_states: HashSet<i32> = HashSet::new();
-        f_states.extend(self.epsilon_chain[&state_a].0.iter());
-        f_states.extend(self.epsilon_chain[&state_b].0.iter());
-        loop {
-            if b_state_stack.len() == 0 {
-                break;
-            }
-            let state = b_state_stack.pop().unwrap();
-            let mut b_states: Vec<i32> = self.epsilon_chain[&state].1.iter().cloned().collect();
-            self.epsilon_chain.get_mut(&state).unwrap().0.extend(&f_states);
-            b_state_stack.append(&mut b_states);
-        }
+    #[test]
+    fn test_quantifier() {
+        let a_nfa = NFA::for_literal("a").ok().unwrap();
+        let nfa = NFA::quantifier(a_nfa.clone(), 2, Some(4)).ok().unwrap();
+        assert!(!nfa.simulate("a".to_string()));
+        assert!(nfa.simulate("aa".to_string()));
+        assert!(nfa.simulate("aaa".to_string()));
+        assert!(nfa.simulate("aaaa".to_string()));
+        assert!(!nfa.simulate("aaaaa".to_string()));
+
+        let nfa = NFA::quantifier(a_nfa.clone(), 3, Some(3)).ok().unwrap();
+        assert!(!nfa.simulate("aa".to_string()));
+        assert!(nfa.simulate("aaa".to_string()));
+        assert!(!nfa.simulate("aaaa".to_string()));
+
+        let nfa = NFA::quantifier(a_nfa.clone(), 3, None).ok().unwrap();
+        assert!(!nfa.simulate("aa".to_string()));
+        assert!(nfa.simulate("aaa".to_string()));
+        assert!(nfa.simulate("a".repeat(10)));
+
+        let nfa = NFA::quantifier(a_nfa, 0, None).ok().unwrap();
+        assert!(nfa.simulate("".to_string()));
+        assert!(nfa.simulate("aa".to_string()));
+        assert!(!nfa.simulate("ab".to_string()));
     }
 
-    /// # 自分が管理する状態かどうかチェック
-    fn check_state(&self, state: &i32) -> bool {
-        if 0 <= *state && *state < NODE_LIMIT as i32 {
-            return self.reserved_state[*state as usize];
-        }
-        false
+    #[test]
+    fn test_union_and_optional() {
+        let nfa_a = NFA::for_literal("ab").ok().unwrap();                                       // (ab|c)?d
+        let nfa_b = NFA::for_literal("c").ok().unwrap();
+        let nfa = NFA::optional(NFA::union(nfa_a, nfa_b).ok().unwrap()).ok().unwrap();
+        let nfa = NFA::concat(nfa, NFA::for_literal("d").ok().unwrap()).ok().unwrap();
+        assert!(nfa.simulate("d".to_string()));
+        assert!(nfa.simulate("abd".to_string()));
+        assert!(nfa.simulate("cd".to_string()));
+        assert!(!nfa.simulate("abcd".to_string()));
+        assert!(!nfa.simulate("ccd".to_string()));
+        assert!(!nfa.simulate("ab".to_string()));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::collections::HashSet;
-    use rand::seq::SliceRandom;
-    use super::NFA;
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_language_is_finite() {
+        let nfa = abb_nfa();
+        assert!(nfa.language_is_infinite());
+        assert!(!nfa.language_is_finite());
+
+        let mut nfa = NFA::new(0, 3).ok().unwrap();       // ab|baを受理するNFA
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 3, 'b');
+        nfa.set_chain(0, 2, 'b');
+        nfa.set_chain(2, 3, 'a');
+        assert!(nfa.language_is_finite());
+
+        nfa.set_chain(1, 0, '@');                       // 0 -a-> 1 -ε-> 0 の閉路ができる
+        assert!(nfa.language_is_infinite());
+
+        let mut nfa = NFA::new(0, 2).ok().unwrap();       // ε遷移のみの閉路を持つaを受理するNFA
+        nfa.set_chain(0, 1, '@');
+        nfa.set_chain(1, 0, '@');
+        nfa.set_chain(1, 2, 'a');
+        assert!(nfa.language_is_finite());
+
+        let mut nfa = NFA::new(0, 1).ok().unwrap();       // 何も受理しないNFA
+        nfa.set_chain(0, 0, 'a');
+        assert!(nfa.language_is_finite());
+    }
 
     #[test]
-    fn test_init() {
-        let nfa = NFA::new(0, 4);
-        assert_eq!(nfa.start, 0);
-        assert_eq!(nfa.finish, 4);
+    #[allow(unused_must_use)]
+    fn test_dead_states() {
+        let mut nfa = NFA::new(0, 5).ok().unwrap();       // acを受理し, 3, 4が行き止まりとなるNFA
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 5, 'c');
+        nfa.set_chain(0, 3, 'b');
+        nfa.set_chain(3, 3, 'b');
+        nfa.set_chain(1, 4, '@');
+        nfa.set_chain(4, 3, 'x');
+        assert_eq!(nfa.dead_states(), vec![2, 3, 4]);
+        assert!(nfa.has_dead_states());
+        assert_eq!(nfa.sink_state(), Some(2));
+
+        let trimmed = nfa.trim();
+        assert!(trimmed.dead_states().is_empty());
+        assert!(!trimmed.has_dead_states());
+        assert_eq!(trimmed.sink_state(), None);
+        assert!(trimmed.equivalent(&nfa));
+
+        let mut nfa = NFA::new(0, 1).ok().unwrap();       // 受理状態は自己ループを持ってもトラップ状態ではない
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 1, 'a');
+        assert_eq!(nfa.sink_state(), None);
     }
 
     #[test]
     #[allow(unused_must_use)]
-    fn test_merge_expand_nfa() {
-        let testcases = vec![
-            ((0, 10), (11, 20)),        // #1
-            ((0, 4), (2, 10)),          // #2
-        ];
-        let expect_results = vec![true, false];
-        for (testcase, result) in testcases.iter().zip(expect_results.iter()) {
-            let nfa_a = NFA::new((testcase.0).0, (testcase.0).1);
-            let nfa_b = NFA::new((testcase.1).0, (testcase.1).1);
-            assert_eq!(*result, NFA::merge(nfa_a, nfa_b, 0, 1).is_ok());
+    fn test_trim() {
+        let mut nfa = NFA::new(0, 7).ok().unwrap();       // ab|cを受理するNFA
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 2, 'b');
+        nfa.set_chain(0, 2, 'c');
+        nfa.set_chain(0, 3, 'd');       // 3は受理状態へ到達できない
+        nfa.set_chain(3, 3, 'd');
+        nfa.set_chain(4, 2, 'e');       // 4は開始状態から到達できない
+        nfa.set_chain(5, 6, '@');       // 5, 6はどちらでもない
+        nfa.set_chain(2, 7, '@');
+        nfa.name_state(1, "after_a");
+        nfa.name_state(3, "dead");
+
+        assert!(nfa.reachable_states().contains(&3));
+        assert!(!nfa.reachable_states().contains(&4));
+        assert!(nfa.productive_states().contains(&4));
+        assert!(!nfa.productive_states().contains(&3));
+
+        let trimmed = nfa.trim();
+        assert!(trimmed.move_table.len() < nfa.move_table.len());
+        let mut states = trimmed.move_table.keys().cloned().collect::<Vec<i32>>();
+        states.sort();
+        assert_eq!(states, vec![0, 1, 2, 7]);
+        assert_eq!(trimmed.state_name(1), Some("after_a"));
+        assert_eq!(trimmed.state_name(3), None);
+        for s in &["ab", "c", "", "a", "d", "dd", "abc"] {
+            assert_eq!(trimmed.simulate(s.to_string()), nfa.simulate(s.to_string()));
         }
+        assert!(trimmed.equivalent(&nfa));
     }
 
     #[test]
     #[allow(unused_must_use)]
-    fn test_merge_chain_process() {
-        let mut nfa_a = NFA::new(1, 6);     // (a|b)*
+    fn test_name_state() {
+        let mut nfa = NFA::new(0, 2).ok().unwrap();
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 2, '@');
+        assert_eq!(nfa.name_state(0, "q0"), Ok(()));
+        assert_eq!(nfa.name_state(2, "accept"), Ok(()));
+        assert_eq!(nfa.name_state(3, "unknown"), Err(NFAError::NonReservedState));
+        assert_eq!(nfa.state_name(0), Some("q0"));
+        assert_eq!(nfa.state_name(1), None);
+
+        let dot = nfa.to_dot();
+        assert!(dot.starts_with("digraph NFA {"));
+        assert!(dot.contains("0 [label=\"q0\", shape=circle];"));
+        assert!(dot.contains("1 [label=\"1\", shape=circle];"));
+        assert!(dot.contains("2 [label=\"accept\", shape=doublecircle];"));
+        assert!(dot.contains("start -> 0;"));
+        assert!(dot.contains("0 -> 1 [label=\"a\"];"));
+        assert!(dot.contains("1 -> 2 [label=\"ε\"];"));
+
+        let mermaid = nfa.to_mermaid();
+        assert!(mermaid.starts_with("stateDiagram-v2\n"));
+        assert!(mermaid.contains("    state \"q0\" as s0\n"));
+        assert!(mermaid.contains("    state \"1\" as s1\n"));
+        assert!(mermaid.contains("    s0 --> s1 : a\n"));
+        assert!(mermaid.contains("    s1 --> s2 : ε\n"));
+        nfa.set_chain(0, 2, ':');
+        nfa.name_state(1, "say \"hi\"");
+        let mermaid = nfa.to_mermaid();
+        assert!(mermaid.contains("    s0 --> s2 : #58;\n"));
+        assert!(mermaid.contains("    state \"say #quot;hi#quot;\" as s1\n"));
+
+        let cloned = nfa.clone();
+        assert_eq!(cloned.state_name(2), Some("accept"));
+        assert_eq!(cloned, nfa);
+        let mut renamed = nfa.clone();
+        renamed.name_state(2, "finish");
+        assert_ne!(renamed, nfa);
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_merge_into() {
+        let mut nfa_a = NFA::new(0, 2).ok().unwrap();     // (a|b)
+        nfa_a.set_chain(0, 1, 'a');
+        nfa_a.set_chain(0, 1, 'b');
         nfa_a.set_chain(1, 2, '@');
-        nfa_a.set_chain(1, 4, '@');
-        nfa_a.set_chain(2, 3, 'a');
-        nfa_a.set_chain(4, 5, 'b');
-        nfa_a.set_chain(3, 6, '@');
-        nfa_a.set_chain(5, 6, '@');
-        nfa_a.set_chain(6, 1, '@');
 
-        let mut nfa_b = NFA::new(7, 10);     // aa|bb
-        nfa_b.set_chain(7, 8, 'a');
-        nfa_b.set_chain(8, 10, 'a');
-        nfa_b.set_chain(7, 9, 'b');
-        nfa_b.set_chain(9, 10, 'b');
+        let mut nfa_b = NFA::new(0, 2).ok().unwrap();     // cd
+        nfa_b.set_chain(0, 1, 'c');
+        nfa_b.set_chain(1, 2, 'd');
 
-        let merged_nfa = NFA::merge(nfa_a, nfa_b, 6, 7).ok().unwrap();
-        assert_eq!(merged_nfa.simulate("aaaaaa".to_string()), true);
-        assert_eq!(merged_nfa.simulate("aaaabb".to_string()), true);
-        assert_eq!(merged_nfa.simulate("abababaab".to_string()), false);
-        assert_eq!(merged_nfa.simulate("abababaabba".to_string()), false);
+        assert!(nfa_a.merge_into(nfa_b.clone(), 2, 5).is_err());
+        assert_eq!(nfa_a.merge_into(nfa_b, 2, 0), Ok(()));
+        assert_eq!(nfa_a.start, 0);
+        assert!(nfa_a.simulate("acd".to_string()));
+        assert!(nfa_a.simulate("bcd".to_string()));
+        assert!(!nfa_a.simulate("ab".to_string()));
+        assert!(!nfa_a.simulate("a".to_string()));
+        assert!(!nfa_a.simulate("cd".to_string()));
+
+        let mut nfa_c = NFA::new(10, 11).ok().unwrap();   // 重複しない状態はそのまま取り込まれる
+        nfa_c.set_chain(10, 11, 'e');
+        nfa_a.merge_into(nfa_c, nfa_a.finish, 10);
+        assert_eq!(nfa_a.finish, 11);
+        assert!(nfa_a.simulate("acde".to_string()));
     }
 
     #[test]
     #[allow(unused_must_use)]
     fn test_get_closure() {
-        let mut nfa = NFA::new(1, 4);
+        let mut nfa = NFA::new(1, 4).ok().unwrap();
         nfa.set_chain(1, 2, 'a');
         nfa.set_chain(1, 3, 'a');
         nfa.set_chain(2, 4, 'b');
@@ -300,7 +3000,7 @@ mod tests {
         states.insert(3);
         states.insert(5);
         states.insert(6);
-        let mut nfa = NFA::new(1, 6);
+        let mut nfa = NFA::new(1, 6).ok().unwrap();
         nfa.set_chain(1, 2, '@');
         nfa.set_chain(2, 3, '@');
         nfa.set_chain(3, 4, '@');
@@ -312,8 +3012,29 @@ mod tests {
 
     #[test]
     #[allow(unused_must_use)]
+    fn test_get_epsilon_closure_single() {
+        let mut nfa = NFA::new(1, 5).ok().unwrap();
+        nfa.set_chain(1, 2, '@');
+        nfa.set_chain(2, 3, '@');
+        nfa.set_chain(3, 4, 'a');
+        nfa.set_chain(4, 5, '@');
+        let sorted = |states: HashSet<i32>| {
+            let mut states = states.into_iter().collect::<Vec<i32>>();
+            states.sort();
+            states
+        };
+        assert_eq!(sorted(nfa.get_epsilon_closure_single(1)), vec![2, 3]);
+        assert_eq!(sorted(nfa.get_epsilon_closure_single(3)), Vec::<i32>::new());
+        assert_eq!(sorted(nfa.get_epsilon_closure_single(4)), vec![5]);
+        assert_eq!(sorted(nfa.get_epsilon_closure_single(100)), Vec::<i32>::new());
+        assert_eq!(sorted(nfa.get_closure(&3, &'a')), vec![4]);
+        assert_eq!(sorted(nfa.get_closure(&100, &'a')), Vec::<i32>::new());
+    }
+
+    #[test]
+    #[allow(unused_must_use, clippy::useless_vec)]
     fn test_epsilon_chain() {
-        let mut nfa = NFA::new(1, 6);
+        let mut nfa = NFA::new(1, 6).ok().unwrap();
         // パス構成
         let mut rng = rand::thread_rng();
         let mut chains = vec![(1, 2), (2, 3), (3, 4), (3, 5), (3, 6), (5, 6)];
@@ -331,8 +3052,44 @@ mod tests {
 
     #[test]
     #[allow(unused_must_use)]
+    fn test_epsilon_chain_out_of_order() {
+        let sorted = |states: HashSet<i32>| {
+            let mut states = states.into_iter().collect::<Vec<i32>>();
+            states.sort();
+            states
+        };
+
+        // 既存のε遷移の手前にε遷移を追加する
+        let mut nfa = NFA::new(1, 6).ok().unwrap();
+        nfa.set_chain(5, 6, '@');
+        nfa.set_chain(1, 3, '@');
+        nfa.set_chain(3, 5, '@');
+        assert_eq!(sorted(nfa.get_epsilon_closure_single(1)), vec![3, 5, 6]);
+        assert_eq!(sorted(nfa.get_epsilon_closure_single(3)), vec![5, 6]);
+
+        // ε遷移の循環
+        nfa.set_chain(6, 1, '@');
+        assert_eq!(sorted(nfa.get_epsilon_closure_single(5)), vec![1, 3, 5, 6]);
+        assert_eq!(sorted(nfa.get_epsilon_closure_single(1)), vec![1, 3, 5, 6]);
+        nfa.set_chain(6, 2, 'a');
+        assert!(nfa.simulate("".to_string()));
+        assert!(!nfa.simulate("a".to_string()));
+
+        // 空文字列を受理するNFAの繰り返し
+        let nullable = NFA::optional(NFA::for_literal("a").ok().unwrap()).ok().unwrap();
+        let nfa = NFA::kleene_star(nullable.clone()).ok().unwrap();
+        assert!(nfa.simulate("".to_string()));
+        assert!(nfa.simulate("aaa".to_string()));
+        let nfa = NFA::one_or_more(nullable).ok().unwrap();
+        assert!(nfa.simulate("".to_string()));
+        assert!(nfa.simulate("aa".to_string()));
+        assert!(!nfa.simulate("ab".to_string()));
+    }
+
+    #[test]
+    #[allow(unused_must_use, clippy::bool_assert_comparison)]
     fn test_simulate() {
-        let mut nfa = NFA::new(0, 10);      // (a|b)* abbを受理するNFA
+        let mut nfa = NFA::new(0, 10).ok().unwrap();      // (a|b)* abbを受理するNFA
         nfa.set_chain(0, 7, '@');
         nfa.set_chain(0, 1, '@');
         nfa.set_chain(1, 2, '@');
@@ -353,5 +3110,615 @@ mod tests {
         assert_eq!(nfa.simulate("bbbbbbaaabb".to_string()), true);
         assert_eq!(nfa.simulate("aaaaaaaaaaaaaaaaaaab".to_string()), false);
         assert_eq!(nfa.simulate("abababababaaabbabababba".to_string()), false);
+        assert_eq!(nfa.simulate("cabb".to_string()), false);
+        assert_eq!(nfa.simulate("abbc".to_string()), false);
+    }
+
+    #[test]
+    fn test_state_image() {
+        let nfa = abb_nfa();
+        let sorted = |states: HashSet<i32>| {
+            let mut states = states.into_iter().collect::<Vec<i32>>();
+            states.sort();
+            states
+        };
+        let initial = nfa.simulate_state("");
+        assert_eq!(sorted(initial.clone()), vec![0, 1, 2, 4, 7]);
+        let after_a = nfa.state_image(&initial, 'a');
+        assert_eq!(sorted(after_a.clone()), vec![1, 2, 3, 4, 6, 7, 8]);
+        let after_ab = nfa.state_image(&after_a, 'b');
+        assert_eq!(sorted(after_ab.clone()), vec![1, 2, 4, 5, 6, 7, 9]);
+        let after_abb = nfa.state_image(&after_ab, 'b');
+        assert_eq!(sorted(after_abb.clone()), vec![1, 2, 4, 5, 6, 7, 10]);
+        assert_eq!(after_abb, nfa.simulate_state("abb"));
+        assert_eq!(sorted(nfa.state_image(&initial, 'c')), Vec::<i32>::new());
+        assert_eq!(sorted(nfa.state_image(&HashSet::new(), 'a')), Vec::<i32>::new());
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_rename_states() {
+        let mut nfa = NFA::new(0, 3).ok().unwrap();       // ab*cを受理するNFA
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 1, 'b');
+        nfa.set_chain(1, 2, '@');
+        nfa.set_chain(2, 3, 'c');
+        nfa.name_state(1, "loop");
+
+        let shifted = nfa.rename_states_offset(500).ok().unwrap();
+        assert_eq!((shifted.start, shifted.finish), (500, 503));
+        assert_eq!(shifted.to_transition_table(), vec![(500, 501, 'a'), (501, 501, 'b'), (501, 502, '@'), (502, 503, 'c')]);
+        for target in &["ac", "abbc", "a", "bc", ""] {
+            assert_eq!(shifted.simulate(target.to_string()), nfa.simulate(target.to_string()));
+        }
+        assert!(shifted.to_dot().contains("loop"));
+        assert_eq!(nfa.rename_states_offset(-1), Err(NFAError::StateIndexOutOfRange { state: -1 }));
+        assert!(matches!(nfa.rename_states_offset(NODE_LIMIT as i32 - 2), Err(NFAError::StateIndexOutOfRange { .. })));
+
+        let mapping: HashMap<i32, i32> = vec![(0, 3), (1, 2), (2, 1), (3, 0)].into_iter().collect();
+        let renamed = nfa.rename_states(&mapping).ok().unwrap();
+        assert_eq!((renamed.start, renamed.finish), (3, 0));
+        assert!(renamed.simulate("abc".to_string()));
+        assert!(renamed.equivalent(&nfa));
+
+        let missing: HashMap<i32, i32> = vec![(0, 10), (1, 11), (3, 13)].into_iter().collect();
+        assert_eq!(nfa.rename_states(&missing), Err(NFAError::NonReservedState));
+        let duplicated: HashMap<i32, i32> = vec![(0, 10), (1, 11), (2, 11), (3, 13)].into_iter().collect();
+        assert_eq!(nfa.rename_states(&duplicated), Err(NFAError::AlreadyReservedState));
+    }
+
+    #[test]
+    fn test_reverse() {
+        let nfa = NFA::concat(
+            NFA::for_literal("ab").ok().unwrap(),
+            NFA::optional(NFA::for_literal("c").ok().unwrap()).ok().unwrap()
+        ).ok().unwrap();        // abc?
+        let reversed = nfa.reverse();
+        assert!(reversed.simulate("ba".to_string()));
+        assert!(reversed.simulate("cba".to_string()));
+        assert!(!reversed.simulate("ab".to_string()));
+        assert!(!reversed.simulate("abc".to_string()));
+        assert!(reversed.reverse().equivalent(&nfa));
+    }
+
+    #[test]
+    fn test_simulate_anchored() {
+        let nfa = NFA::concat(
+            NFA::for_literal("ab").ok().unwrap(),
+            NFA::kleene_star(NFA::for_literal("b").ok().unwrap()).ok().unwrap()
+        ).ok().unwrap();        // abb*
+        assert_eq!(nfa.simulate_anchored_start("abbbc"), Some(4));
+        assert_eq!(nfa.simulate_anchored_start("cabb"), None);
+        assert_eq!(nfa.simulate_anchored_end("cabb"), Some(1));
+        assert_eq!(nfa.simulate_anchored_end("abbbc"), None);
+        assert_eq!(nfa.simulate_anchored_end("ababb"), Some(2));
+        assert_eq!(nfa.simulate_anchored_end("あab"), Some(3));
+
+        let nfa = NFA::kleene_star(NFA::for_literal("a").ok().unwrap()).ok().unwrap();      // a*
+        assert_eq!(nfa.simulate_anchored_start("baa"), Some(0));
+        assert_eq!(nfa.simulate_anchored_end("baa"), Some(1));
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_simulate_suffix() {
+        let mut nfa = NFA::new(0, 3).ok().unwrap();       // abbを受理するNFA
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 2, 'b');
+        nfa.set_chain(2, 3, 'b');
+        assert!(nfa.simulate_suffix("xyzabb"));
+        assert!(nfa.simulate_suffix("abb"));
+        assert!(!nfa.simulate_suffix("abbxyz"));
+        assert!(!nfa.simulate_suffix(""));
+        assert!(nfa.simulate_contains_pattern("xyzabb"));
+        assert!(nfa.simulate_contains_pattern("abbxyz"));
+        assert!(nfa.simulate_contains_pattern("あabbい"));
+        assert!(!nfa.simulate_contains_pattern("abab"));
+
+        let nfa = NFA::kleene_star(NFA::for_literal("a").ok().unwrap()).ok().unwrap();      // a*
+        assert!(nfa.simulate_suffix("xyz"));
+        assert!(nfa.simulate_contains_pattern(""));
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_simulate_suffix_after_mutation() {
+        let mut nfa = NFA::new(0, 3).ok().unwrap();       // abbを受理するNFA
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 2, 'b');
+        nfa.set_chain(2, 3, 'b');
+        assert!(nfa.simulate_suffix("xabb"));
+        assert!(!nfa.simulate_suffix("xacb"));
+
+        nfa.set_chain(1, 2, 'c');                          // a(b|c)b
+        assert!(nfa.simulate_suffix("xacb"));
+
+        let other = NFA::for_literal("d").ok().unwrap();
+        nfa.merge_into(other, 3, 0);                        // a(b|c)bd
+        assert!(nfa.simulate_suffix("xacbd"));
+        assert!(!nfa.simulate_suffix("xacb"));
+
+        nfa.finish = 3;
+        assert!(nfa.simulate_suffix("xacb"));
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_simulate_state() {
+        let mut nfa = NFA::new(0, 3).ok().unwrap();       // ab|acを受理するNFA
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 2, 'b');
+        nfa.set_chain(1, 3, 'c');
+        nfa.set_chain(2, 3, '@');
+        let sorted = |states: HashSet<i32>| {
+            let mut states = states.into_iter().collect::<Vec<i32>>();
+            states.sort();
+            states
+        };
+        assert_eq!(sorted(nfa.simulate_state("")), vec![0]);
+        assert_eq!(sorted(nfa.simulate_state("a")), vec![1]);
+        assert_eq!(sorted(nfa.simulate_state("ab")), vec![2, 3]);
+        assert_eq!(sorted(nfa.simulate_state("ac")), vec![3]);
+        assert_eq!(sorted(nfa.simulate_state("b")), Vec::<i32>::new());
+        assert!(nfa.simulate_state("a").contains(&1) && !nfa.simulate("a".to_string()));
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_simulate_explain() {
+        let mut nfa = NFA::new(0, 3).ok().unwrap();       // ab|acを受理するNFA
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 2, 'b');
+        nfa.set_chain(1, 3, 'c');
+        nfa.set_chain(2, 3, '@');
+        assert_eq!(nfa.simulate_explain("ab"), RejectReason::Accepted);
+        assert_eq!(nfa.simulate_explain("ac"), RejectReason::Accepted);
+        assert_eq!(nfa.simulate_explain("a"), RejectReason::NoAcceptingState { active_states: vec![1] });
+        assert_eq!(nfa.simulate_explain(""), RejectReason::NoAcceptingState { active_states: vec![0] });
+        assert_eq!(nfa.simulate_explain("ad"), RejectReason::DeadState { position: 1, character: 'd' });
+        assert_eq!(nfa.simulate_explain("abc"), RejectReason::DeadState { position: 2, character: 'c' });
+        assert_eq!(nfa.simulate_explain("あb"), RejectReason::DeadState { position: 0, character: 'あ' });
+    }
+
+    #[test]
+    fn test_simulate_bounded() {
+        let a_star = NFA::kleene_star(NFA::for_literal("a").ok().unwrap()).ok().unwrap();
+        let nfa = NFA::concat(NFA::kleene_star(a_star).ok().unwrap(), NFA::for_literal("b").ok().unwrap()).ok().unwrap();    // (a*)*b
+        assert_eq!(nfa.simulate_bounded("aab", 1000), Ok(true));
+        assert_eq!(nfa.simulate_bounded("aaa", 1000), Ok(false));
+        assert_eq!(nfa.simulate_bounded("", 0), Ok(false));
+
+        let target = "a".repeat(10000);
+        assert_eq!(nfa.simulate_bounded(&target, 100), Err(SimulationError::StepLimitExceeded { steps_taken: 100 }));
+        let steps = target.len() * nfa.simulate_state("").len();
+        assert_eq!(nfa.simulate_bounded(&target, steps), Ok(false));
+        assert!(nfa.simulate_bounded(&target, steps - 1).is_err());
+    }
+
+    #[test]
+    fn test_simulate_on_iterator() {
+        let nfa = abb_nfa();
+        for s in &["ababb", "abab", "", "bbabb", "abbc"] {
+            assert_eq!(nfa.simulate_on_chars(s.chars()), nfa.simulate(s.to_string()));
+            assert_eq!(nfa.simulate_on_bytes(s.bytes()), nfa.simulate(s.to_string()));
+        }
+
+        let reader = Cursor::new("ab".repeat(1000) + "abb");
+        assert!(nfa.simulate_on_bytes(reader.bytes().map(|b| b.unwrap())));
+        let reader = Cursor::new("ab".repeat(1000));
+        assert!(!nfa.simulate_on_chars(reader.bytes().map(|b| b.unwrap() as char)));
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_is_deterministic() {
+        let mut nfa = NFA::new(0, 3).ok().unwrap();       // abc
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 2, 'b');
+        nfa.set_chain(2, 3, 'c');
+        assert!(nfa.is_deterministic());
+        assert_eq!(nfa.as_deterministic(), Some(&nfa));
+        nfa.set_chain(0, 2, 'b');
+        assert!(nfa.is_deterministic());
+        nfa.set_chain(0, 3, 'a');
+        assert!(!nfa.is_deterministic());
+        assert_eq!(nfa.as_deterministic(), None);
+
+        let nfa = abb_nfa();
+        assert!(!nfa.is_deterministic());
+
+        let mut nfa = NFA::new(0, 1).ok().unwrap();       // ε
+        nfa.set_chain(0, 1, '@');
+        assert!(!nfa.is_deterministic());
+        assert!(NFA::new(0, 1).ok().unwrap().is_deterministic());
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_nondeterminism_degree() {
+        let mut nfa = NFA::new(0, 3).ok().unwrap();       // abc
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 2, 'b');
+        nfa.set_chain(2, 3, 'c');
+        assert_eq!(nfa.simulate_nondeterminism_degree("abc"), 1);
+        assert_eq!(nfa.simulate_nondeterminism_degree("x"), 1);
+        assert_eq!(nfa.simulate_average_state_set_size("abc"), 1.0);
+        assert_eq!(nfa.simulate_average_state_set_size("ax"), 2.0 / 3.0);
+
+        let nfa = abb_nfa();
+        assert_eq!(nfa.simulate_nondeterminism_degree(""), 5);
+        assert_eq!(nfa.simulate_nondeterminism_degree("ababababb"), 7);
+        assert!(nfa.simulate_average_state_set_size("ababababb") > 1.0);
+    }
+
+    #[test]
+    fn test_simulate_from_char() {
+        let nfa = NFA::concat(NFA::for_literal("b").ok().unwrap(), NFA::kleene_star(NFA::for_literal("c").ok().unwrap()).ok().unwrap()).ok().unwrap();    // bc*
+        assert!(nfa.simulate_from_char("abcc", 1));
+        assert!(!nfa.simulate_from_char("abcc", 0));
+        assert!(!nfa.simulate_from_char("abcc", 2));
+        assert!(nfa.simulate_from_char("あいbc", 2));
+        assert!(!nfa.simulate_from_char("あいbc", 6));
+        assert!(!nfa.simulate_from_char("ab", 2));
+
+        let nfa = NFA::kleene_star(NFA::for_literal("a").ok().unwrap()).ok().unwrap();     // a*
+        assert!(nfa.simulate_from_char("ba", 1));
+        assert!(!nfa.simulate_from_char("ba", 2));
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-normalization")]
+    fn test_simulate_normalized_unicode() {
+        use super::NormalizationForm;
+
+        let nfa = NFA::for_literal("caf\u{e9}").ok().unwrap();       // caféを受理するNFA (合成済み文字)
+        assert!(!nfa.simulate("cafe\u{301}".to_string()));
+        assert!(nfa.simulate_normalized_unicode("cafe\u{301}", NormalizationForm::NFC));
+        assert!(nfa.simulate_normalized_unicode("caf\u{e9}", NormalizationForm::NFKC));
+        assert!(!nfa.simulate_normalized_unicode("caf\u{e9}", NormalizationForm::NFD));
+
+        let nfa = NFA::for_literal("e\u{301}").ok().unwrap();        // 結合文字列を受理するNFA
+        assert!(nfa.simulate_normalized_unicode("\u{e9}", NormalizationForm::NFD));
+        assert!(!nfa.simulate_normalized_unicode("\u{e9}", NormalizationForm::NFC));
+
+        let nfa = NFA::for_literal("fi1").ok().unwrap();              // 互換分解でのみ一致する入力
+        assert!(!nfa.simulate_normalized_unicode("\u{fb01}\u{2460}", NormalizationForm::NFC));
+        assert!(nfa.simulate_normalized_unicode("\u{fb01}\u{2460}", NormalizationForm::NFKC));
+        assert!(nfa.simulate_normalized_unicode("\u{fb01}\u{2460}", NormalizationForm::NFKD));
+    }
+
+    #[test]
+    #[cfg(feature = "ndarray-export")]
+    #[allow(unused_must_use)]
+    fn test_adjacency_matrix() {
+        let mut nfa = NFA::new(0, 2).ok().unwrap();       // a(b|@)を受理するNFA
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 2, 'b');
+        nfa.set_chain(1, 2, '@');
+        let alphabet = ['a', 'b', '@'];
+        let matrix = nfa.to_adjacency_matrix(&alphabet);
+        assert_eq!(matrix.dim(), (3, 3, 3));
+        assert_eq!(matrix.iter().filter(|value| **value != 0).count(), 3);
+        assert_eq!(matrix[[0, 0, 1]], 1);
+        assert_eq!(matrix[[1, 1, 2]], 1);
+        assert_eq!(matrix[[1, 2, 2]], 1);
+        assert_eq!(matrix[[0, 1, 1]], 0);
+        assert_eq!(nfa.to_adjacency_matrix(&['a']).iter().filter(|value| **value != 0).count(), 1);
+
+        let restored = NFA::from_adjacency_matrix(0, 2, matrix.view(), &alphabet).ok().unwrap();
+        assert_eq!(restored.to_transition_table(), nfa.to_transition_table());
+        assert!(restored.equivalent(&nfa));
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_simulate_parallel() {
+        let nfa = NFA::concat(
+            NFA::kleene_star(NFA::for_char_set(&['a'..='b'])).ok().unwrap(),
+            NFA::for_literal("abb").ok().unwrap()
+        ).ok().unwrap();        // (a|b)*abb
+        let targets: Vec<String> = (0..2000)
+            .map(|n| format!("{:b}", n).replace('0', "a").replace('1', "b"))
+            .collect();
+        let targets: Vec<&str> = targets.iter().map(|target| target.as_str()).collect();
+        let expected: Vec<bool> = targets.iter().map(|target| nfa.simulate(target.to_string())).collect();
+        assert_eq!(nfa.simulate_parallel(&targets), expected);
+        assert_eq!(nfa.simulate_parallel_count(&targets), expected.iter().filter(|b| **b).count());
+        assert!(nfa.simulate_parallel(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        let nfa = NFA::concat(
+            NFA::for_literal("abc").ok().unwrap(),
+            NFA::for_literal("-D").ok().unwrap()
+        ).ok().unwrap();        // abc-D
+        assert!(nfa.simulate_case_insensitive("ABC-D"));
+        assert!(nfa.simulate_case_insensitive("AbC-d"));
+        assert!(!nfa.simulate_case_insensitive("ABD-D"));
+        assert!(!nfa.simulate("ABC-D".to_string()));
+
+        let mut nfa = nfa;
+        nfa.build_case_insensitive().ok().unwrap();
+        assert!(nfa.simulate("ABC-D".to_string()));
+        assert!(nfa.simulate("AbC-d".to_string()));
+        assert!(nfa.simulate("abc-D".to_string()));
+        assert!(!nfa.simulate("ABD-D".to_string()));
+    }
+
+    #[test]
+    fn test_simulate_with_captures() {
+        let a_part = NFA::one_or_more(NFA::for_literal("a").ok().unwrap()).ok().unwrap();     // a+
+        let b_part = NFA::one_or_more(NFA::for_literal("b").ok().unwrap()).ok().unwrap();     // b+
+        let offset = a_part.max_state() + 1 - b_part.min_state();
+        let groups = [(a_part.start, a_part.finish), (b_part.start + offset, b_part.finish + offset)];
+        let nfa = NFA::concat(a_part, b_part).ok().unwrap();        // (a+)(b+)
+
+        assert_eq!(nfa.simulate_with_captures("aaabbb", &groups), Some(vec![(0, 3), (3, 6)]));
+        assert_eq!(nfa.simulate_with_captures("ab", &groups), Some(vec![(0, 1), (1, 2)]));
+        assert_eq!(nfa.simulate_with_captures("aaa", &groups), None);
+        assert_eq!(nfa.simulate_with_captures("abab", &groups), None);
+        assert_eq!(nfa.simulate_with_captures("aabbb", &[]), Some(vec![]));
+    }
+
+    #[test]
+    fn test_simulate_with_trace() {
+        let nfa = abb_nfa();
+
+        let (accepted, trace) = nfa.simulate_with_trace("aabb");
+        assert!(accepted);
+        assert_eq!(trace.len(), 4);
+        assert_eq!(trace[0], vec![1, 2, 3, 4, 6, 7, 8]);
+        assert_eq!(trace[2], vec![1, 2, 4, 5, 6, 7, 9]);
+        assert!(trace[3].contains(&nfa.finish));
+
+        let (accepted, trace) = nfa.simulate_with_trace("aab");
+        assert!(!accepted);
+        assert_eq!(trace.len(), 3);
+        assert!(!trace[2].contains(&nfa.finish));
+
+        let (accepted, trace) = nfa.simulate_with_trace("");
+        assert!(!accepted);
+        assert!(trace.is_empty());
+    }
+
+    #[test]
+    fn test_do_match() {
+        let nfa = NFA::concat(
+            NFA::for_literal("ab").ok().unwrap(),
+            NFA::kleene_star(NFA::for_literal("b").ok().unwrap()).ok().unwrap()
+        ).ok().unwrap();        // abb*
+        let m = nfa.do_match("あcabbbab").unwrap();
+        assert_eq!((m.start(), m.end()), (4, 8));
+        assert_eq!(m.as_str(), "abbb");
+        assert_eq!(m.len(), 4);
+        assert!(!m.is_empty());
+        assert!(nfa.do_match("aacb").is_none());
+
+        let nfa = NFA::kleene_star(NFA::for_literal("a").ok().unwrap()).ok().unwrap();      // a*
+        let m = nfa.do_match("baa").unwrap();
+        assert_eq!((m.start(), m.end()), (0, 0));
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_simulate_leftmost() {
+        let nfa = NFA::one_or_more(NFA::for_literal("a").ok().unwrap()).ok().unwrap();      // a+
+        assert_eq!(nfa.simulate_leftmost_longest("bbaaa"), Some((2, 5)));
+        assert_eq!(nfa.simulate_leftmost_shortest("bbaaa"), Some((2, 3)));
+        assert_eq!(nfa.simulate_leftmost_longest("bbb"), None);
+        assert_eq!(nfa.simulate_leftmost_shortest("bbb"), None);
+
+        let nfa = NFA::union(
+            NFA::for_literal("ab").ok().unwrap(),
+            NFA::for_literal("abcd").ok().unwrap()
+        ).ok().unwrap();        // ab|abcd
+        assert_eq!(nfa.simulate_leftmost_longest("xabcde"), Some((1, 5)));
+        assert_eq!(nfa.simulate_leftmost_shortest("xabcde"), Some((1, 3)));
+    }
+
+    #[test]
+    fn test_accepting_words_up_to_length() {
+        let nfa = abb_nfa();
+        let words = nfa.accepting_words_up_to_length(5);
+        assert!(words.contains(&"abb".to_string()));
+        assert!(words.contains(&"aabb".to_string()));
+        assert!(words.contains(&"babb".to_string()));
+        assert!(!words.contains(&"ab".to_string()));
+        assert!(!words.contains(&"abba".to_string()));
+        assert_eq!(words.len(), 1 + 2 + 4);
+        assert_eq!(&words[..3], &["abb", "aabb", "babb"]);
+        assert!(nfa.accepting_words_up_to_length(2).is_empty());
+
+        let nfa = NFA::kleene_star(NFA::for_literal("a").ok().unwrap()).ok().unwrap();      // a*
+        assert_eq!(nfa.accepting_words_up_to_length(2), vec!["", "a", "aa"]);
+
+        // 長さ30以下の接頭辞は2^30通りあるが, 状態集合は深さごとに1つずつしかない
+        let a_or_b = NFA::for_char_set(&['a'..='b']);
+        let nfa = NFA::concat(NFA::for_repeat(&a_or_b, 30).ok().unwrap(), NFA::for_literal("c").ok().unwrap()).ok().unwrap();
+        assert!(nfa.accepting_words_up_to_length(30).is_empty());
+        let nfa = NFA::concat(NFA::for_repeat(&a_or_b, 2).ok().unwrap(), NFA::for_literal("c").ok().unwrap()).ok().unwrap();
+        assert_eq!(nfa.accepting_words_up_to_length(5), vec!["aac", "abc", "bac", "bbc"]);
+    }
+
+    #[test]
+    fn test_simulate_all_matches() {
+        let nfa = NFA::for_literal("abb").ok().unwrap();
+        let matches = nfa.simulate_all_matches("aababbaabbbabb");
+        assert_eq!(matches, vec![(3, 6), (7, 10), (11, 14)]);
+        for window in matches.windows(2) {
+            assert!(window[0].1 <= window[1].0);
+        }
+        assert_eq!(nfa.simulate_all_matches("ab"), vec![]);
+
+        let nfa = NFA::union(
+            NFA::for_literal("ab").ok().unwrap(),
+            NFA::for_literal("aba").ok().unwrap()
+        ).ok().unwrap();        // ab|aba
+        assert_eq!(nfa.find_all_non_overlapping("ababab"), vec![(0, 3), (4, 6)]);
+
+        let nfa = NFA::kleene_star(NFA::for_literal("a").ok().unwrap()).ok().unwrap();      // a*
+        assert_eq!(nfa.simulate_all_matches("baab"), vec![(0, 0), (1, 3), (3, 3), (4, 4)]);
+        assert_eq!(nfa.simulate_all_matches("あaa"), vec![(0, 0), (3, 5), (5, 5)]);
+    }
+
+    #[test]
+    fn test_intersect_language_nonempty() {
+        let a = NFA::concat(
+            NFA::kleene_star(NFA::for_literal("a").ok().unwrap()).ok().unwrap(),
+            NFA::optional(NFA::for_literal("b").ok().unwrap()).ok().unwrap()
+        ).ok().unwrap();        // a*b?
+        let b = NFA::union(
+            NFA::for_literal("aab").ok().unwrap(),
+            NFA::for_literal("c").ok().unwrap()
+        ).ok().unwrap();        // aab|c
+        let c = NFA::one_or_more(NFA::for_literal("ba").ok().unwrap()).ok().unwrap();     // (ba)+
+        assert!(a.intersect_language_nonempty(&b));
+        assert!(b.intersect_language_nonempty(&a));
+        assert!(!a.intersect_language_nonempty(&c));
+        assert!(!b.intersect_language_nonempty(&c));
+        assert!(c.intersect_language_nonempty(&c));
+
+        let empty = NFA::optional(NFA::for_literal("x").ok().unwrap()).ok().unwrap();     // x?
+        assert!(a.intersect_language_nonempty(&empty));
+        assert!(!c.intersect_language_nonempty(&empty));
+    }
+
+    #[test]
+    fn test_product_construction() {
+        let a = NFA::concat(
+            NFA::kleene_star(NFA::for_literal("a").ok().unwrap()).ok().unwrap(),
+            NFA::optional(NFA::for_literal("b").ok().unwrap()).ok().unwrap()
+        ).ok().unwrap();        // a*b?
+        let b = NFA::union(
+            NFA::for_literal("aa").ok().unwrap(),
+            NFA::for_literal("c").ok().unwrap()
+        ).ok().unwrap();        // aa|c
+        let accepted = |accept: ProductAccept| {
+            let nfa = NFA::product_construction(&a, &b, accept).ok().unwrap();
+            ["", "a", "aa", "aab", "c", "ac", "ba"].iter()
+                .filter(|s| nfa.simulate(s.to_string()))
+                .cloned()
+                .collect::<Vec<&str>>()
+        };
+        assert_eq!(accepted(ProductAccept::Both), vec!["aa"]);
+        assert_eq!(accepted(ProductAccept::AOnly), vec!["", "a", "aab"]);
+        assert_eq!(accepted(ProductAccept::BOnly), vec!["c"]);
+        assert_eq!(accepted(ProductAccept::Either), vec!["", "a", "aa", "aab", "c"]);
+        assert_eq!(accepted(ProductAccept::ExactlyOne), vec!["", "a", "aab", "c"]);
+
+        let both = NFA::product_construction(&a, &a, ProductAccept::Both).ok().unwrap();
+        assert!(both.equivalent(&a));
+        let none = NFA::product_construction(&a, &a, ProductAccept::ExactlyOne).ok().unwrap();
+        assert!(!none.simulate("".to_string()));
+        assert!(!none.simulate("aab".to_string()));
+
+        let a_or_b = NFA::for_char_set(&['a'..='b']);
+        let nfa = NFA::concat(
+            NFA::concat(NFA::kleene_star(a_or_b.clone()).ok().unwrap(), NFA::for_literal("a").ok().unwrap()).ok().unwrap(),
+            NFA::for_repeat(&a_or_b, 10).ok().unwrap()
+        ).ok().unwrap();        // (a|b)*a(a|b){10}
+        assert!(nfa.state_count() < 100);
+        assert!(matches!(
+            NFA::product_construction(&nfa, &nfa, ProductAccept::Both),
+            Err(NFAError::StateIndexOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_equivalent() {
+        let nfa = abb_nfa();
+
+        let a_or_b = NFA::for_char_set(&['a'..='b']);
+        let prefix = NFA::kleene_star(a_or_b).ok().unwrap();
+        let nfa_abb = NFA::concat(prefix.clone(), NFA::for_literal("abb").ok().unwrap()).ok().unwrap();
+        let nfa_ab = NFA::concat(prefix, NFA::for_literal("ab").ok().unwrap()).ok().unwrap();
+
+        assert!(nfa.equivalent(&nfa));
+        assert!(nfa.equivalent(&nfa_abb));
+        assert!(nfa_abb.equivalent(&nfa));
+        assert!(!nfa.equivalent(&nfa_ab));
+        assert!(!nfa_ab.equivalent(&nfa));
+        assert!(!nfa.equivalent(&NFA::for_literal("abb").ok().unwrap()));
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_start_simulation() {
+        let mut nfa = NFA::new(0, 3).ok().unwrap();     // (a|b)* aab
+        let chains = [
+            (0, 0, 'a'), (0, 0, 'b'), (0, 1, 'a'), (1, 1, 'a'), (1, 1, 'b'),
+            (1, 2, 'a'), (2, 2, 'a'), (2, 2, 'b'), (2, 3, 'b'), (2, 0, '@')
+        ];
+        for chain in chains.iter() {
+            nfa.set_chain(chain.0, chain.1, chain.2);
+        }
+
+        let target = "abababbbabababbbaabbaab";
+        let mut simulation = nfa.start_simulation();
+        simulation.feed_str(&target[..7]).feed_str(&target[7..15]).feed_str(&target[15..]);
+        assert_eq!(simulation.is_accepting(), nfa.simulate(target.to_string()));
+        assert!(simulation.is_accepting());
+
+        simulation.feed('a');
+        assert!(!simulation.is_accepting());
+        simulation.reset();
+        assert!(!simulation.is_accepting());
+        assert!(simulation.feed('a').feed('a').feed('b').is_accepting());
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_simulate_count() {
+        let mut nfa = NFA::new(0, 4).ok().unwrap();       // a(b|b)c
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 2, 'b');
+        nfa.set_chain(1, 3, 'b');
+        nfa.set_chain(2, 4, 'c');
+        nfa.set_chain(3, 4, 'c');
+        assert_eq!(nfa.simulate_count("abc"), 2);
+        assert_eq!(nfa.simulate_count("ab"), 0);
+
+        let mut nfa = NFA::new(0, 3).ok().unwrap();       // abc
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 2, 'b');
+        nfa.set_chain(2, 3, 'c');
+        assert_eq!(nfa.simulate_count("abc"), 1);
+        assert_eq!(nfa.simulate_count("abd"), 0);
+
+        let mut nfa = NFA::new(1, 6).ok().unwrap();       // (a|a)*a
+        nfa.set_chain(1, 2, '@');
+        nfa.set_chain(2, 3, 'a');
+        nfa.set_chain(2, 4, 'a');
+        nfa.set_chain(3, 2, '@');
+        nfa.set_chain(4, 2, '@');
+        nfa.set_chain(2, 5, '@');
+        nfa.set_chain(5, 6, 'a');
+        assert_eq!(nfa.simulate_count("a"), 1);
+        assert_eq!(nfa.simulate_count("aaa"), 4);
+        assert_eq!(nfa.simulate_count(""), 0);
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_simulate_transition_events() {
+        let mut nfa = NFA::new(0, 4).ok().unwrap();       // a(b|c)d
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 2, 'b');
+        nfa.set_chain(1, 2, 'c');
+        nfa.set_chain(1, 3, 'b');
+        nfa.set_chain(2, 4, 'd');
+        nfa.set_chain(3, 4, 'd');
+
+        let mut events: Vec<(i32, char, i32)> = vec![];
+        let result = nfa.simulate_transition_events("abd", |f, c, t| events.push((f, c, t)));
+        events.sort();
+        assert!(result);
+        assert_eq!(events, vec![(0, 'a', 1), (1, 'b', 2), (1, 'b', 3), (2, 'd', 4), (3, 'd', 4)]);
+
+        let mut events: Vec<(i32, char, i32)> = vec![];
+        let result = nfa.simulate_transition_events("ax", |f, c, t| events.push((f, c, t)));
+        assert!(!result);
+        assert_eq!(events, vec![(0, 'a', 1)]);
     }
 }
\ No newline at end of file
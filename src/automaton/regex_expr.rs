@@ -0,0 +1,389 @@
+//! # NFAと相互に変換できる正規表現の構文木
+
+#[cfg(feature = "std")]
+use std::collections::{ BTreeMap, BTreeSet };
+#[cfg(not(feature = "std"))]
+use alloc::{ boxed::Box, collections::{ BTreeMap, BTreeSet }, string::{ String, ToString }, vec::Vec };
+use core::fmt;
+use core::iter::Peekable;
+use core::str::CharIndices;
+use super::nfa::{ NFA, NFAError };
+
+/// # 正規表現の構文木
+///
+/// ## variants
+/// - Literal(char) => 1文字
+/// - Concat(RegexExpr, RegexExpr) => 連接
+/// - Alt(RegexExpr, RegexExpr) => 選択
+/// - Star(RegexExpr) => 0回以上の繰り返し
+/// - Epsilon => 空文字列
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegexExpr {
+    Literal(char),
+    Concat(Box<RegexExpr>, Box<RegexExpr>),
+    Alt(Box<RegexExpr>, Box<RegexExpr>),
+    Star(Box<RegexExpr>),
+    Epsilon,
+}
+
+/// # 正規表現の構文解析のエラー
+///
+/// ## variants
+/// - UnexpectedChar => 文法に合わない文字がある (position: その文字の位置 (バイト単位), character: その文字)
+/// - UnexpectedEnd => 式の途中で文字列が終わった
+/// - ReservedChar => ε遷移を表す'@'が文字として使われている (position: その文字の位置 (バイト単位), character: その文字)
+/// - Build => 構文木からNFAを生成できなかった
+#[derive(Debug, PartialEq)]
+pub enum RegexParseError {
+    UnexpectedChar { position: usize, character: char },
+    UnexpectedEnd,
+    ReservedChar { position: usize, character: char },
+    Build(NFAError),
+}
+
+/// # 正規表現でエスケープが必要な文字
+const META_CHARS: &str = "\\()|*+?[]{}.^$∅";
+
+/// # 構文解析中の文字列
+type Chars<'a> = Peekable<CharIndices<'a>>;
+
+/* 自身を引数に取らない関数群 */
+impl RegexExpr {
+    /// # 連接を生成する (空文字列との連接は省略する)
+    fn concat(a: RegexExpr, b: RegexExpr) -> RegexExpr {
+        match (a, b) {
+            (RegexExpr::Epsilon, b) => b,
+            (a, RegexExpr::Epsilon) => a,
+            (a, b) => RegexExpr::Concat(Box::new(a), Box::new(b))
+        }
+    }
+
+    /// # 選択を生成する (同じ式同士の選択は省略する)
+    fn alt(a: RegexExpr, b: RegexExpr) -> RegexExpr {
+        if a == b {
+            a
+        } else {
+            RegexExpr::Alt(Box::new(a), Box::new(b))
+        }
+    }
+
+    /// # 繰り返しを生成する (空文字列や繰り返しの繰り返しは省略する)
+    fn star(a: RegexExpr) -> RegexExpr {
+        match a {
+            RegexExpr::Epsilon => RegexExpr::Epsilon,
+            RegexExpr::Star(_) => a,
+            a => RegexExpr::Star(Box::new(a))
+        }
+    }
+
+    /// # 正規表現の文字列を解析して構文木を生成する
+    ///
+    /// ## note
+    /// - Displayが書き出す文法 (連接, |, *, 括弧, \によるエスケープ) を解析する. 空文字列は"()"と表す
+    /// - 文字列全体が"∅"であれば何も受理しないとしてNoneを返す
+    /// - エスケープされていないメタ文字 (+, ?, [など) はUnexpectedCharとなる
+    /// - '@'はε遷移を表すため, エスケープの有無に関わらずReservedCharとなる
+    ///
+    /// ## args
+    /// - pattern: &str => 正規表現
+    ///
+    /// ## returns
+    /// Result<Option<RegexExpr>, RegexParseError>
+    pub fn parse(pattern: &str) -> Result<Option<RegexExpr>, RegexParseError> {
+        if pattern == "∅" {
+            return Ok(None);
+        }
+        let mut chars = pattern.char_indices().peekable();
+        let expr = Self::parse_alt(&mut chars)?;
+        match chars.next() {
+            Some((position, character)) => Err(RegexParseError::UnexpectedChar { position, character }),
+            None => Ok(Some(expr))
+        }
+    }
+
+    /// # 選択 (連接を|で並べたもの) を解析する
+    fn parse_alt(chars: &mut Chars) -> Result<RegexExpr, RegexParseError> {
+        let mut expr = Self::parse_concat(chars)?;
+        while let Some((_, '|')) = chars.peek() {
+            chars.next();
+            expr = RegexExpr::Alt(Box::new(expr), Box::new(Self::parse_concat(chars)?));
+        }
+        Ok(expr)
+    }
+
+    /// # 連接 (1つ以上の繰り返しを並べたもの) を解析する
+    fn parse_concat(chars: &mut Chars) -> Result<RegexExpr, RegexParseError> {
+        let mut expr = Self::parse_star(chars)?;
+        while let Some((_, c)) = chars.peek() {
+            if *c == '|' || *c == ')' {
+                break;
+            }
+            expr = RegexExpr::Concat(Box::new(expr), Box::new(Self::parse_star(chars)?));
+        }
+        Ok(expr)
+    }
+
+    /// # 繰り返し (*が0個以上続く原子) を解析する
+    fn parse_star(chars: &mut Chars) -> Result<RegexExpr, RegexParseError> {
+        let mut expr = Self::parse_atom(chars)?;
+        while let Some((_, '*')) = chars.peek() {
+            chars.next();
+            expr = RegexExpr::Star(Box::new(expr));
+        }
+        Ok(expr)
+    }
+
+    /// # 原子 (1文字, エスケープされた文字, 括弧で囲まれた式) を解析する
+    fn parse_atom(chars: &mut Chars) -> Result<RegexExpr, RegexParseError> {
+        match chars.next() {
+            Some((_, '(')) => {
+                if let Some((_, ')')) = chars.peek() {
+                    chars.next();
+                    return Ok(RegexExpr::Epsilon);
+                }
+                let expr = Self::parse_alt(chars)?;
+                match chars.next() {
+                    Some((_, ')')) => Ok(expr),
+                    Some((position, character)) => Err(RegexParseError::UnexpectedChar { position, character }),
+                    None => Err(RegexParseError::UnexpectedEnd)
+                }
+            }
+            Some((_, '\\')) => match chars.next() {
+                Some((position, '@')) => Err(RegexParseError::ReservedChar { position, character: '@' }),
+                Some((_, c)) => Ok(RegexExpr::Literal(c)),
+                None => Err(RegexParseError::UnexpectedEnd)
+            },
+            Some((position, '@')) => Err(RegexParseError::ReservedChar { position, character: '@' }),
+            Some((position, character)) if META_CHARS.contains(character) => {
+                Err(RegexParseError::UnexpectedChar { position, character })
+            }
+            Some((_, c)) => Ok(RegexExpr::Literal(c)),
+            None => Err(RegexParseError::UnexpectedEnd)
+        }
+    }
+}
+
+/* 自身を引数にとるメソッド群 */
+impl RegexExpr {
+    /// # 同じ言語を受理するNFAを生成する
+    ///
+    /// ## returns
+    /// Result<NFA, NFAError>
+    pub fn to_nfa(&self) -> Result<NFA, NFAError> {
+        match self {
+            RegexExpr::Literal(c) => Ok(NFA::for_char_set(&[*c..=*c])),
+            RegexExpr::Concat(a, b) => NFA::concat(a.to_nfa()?, b.to_nfa()?),
+            RegexExpr::Alt(a, b) => NFA::union(a.to_nfa()?, b.to_nfa()?),
+            RegexExpr::Star(a) => NFA::kleene_star(a.to_nfa()?),
+            RegexExpr::Epsilon => NFA::new(0, 0)
+        }
+    }
+
+    /// # 結合の強さ (大きいほど強い)
+    fn precedence(&self) -> u8 {
+        match self {
+            RegexExpr::Alt(_, _) => 0,
+            RegexExpr::Concat(_, _) => 1,
+            RegexExpr::Star(_) => 2,
+            RegexExpr::Literal(_) | RegexExpr::Epsilon => 3
+        }
+    }
+
+    /// # 結合の強さがmin未満であれば括弧で囲んで書き出す
+    fn fmt_with_precedence(&self, f: &mut fmt::Formatter, min: u8) -> fmt::Result {
+        if self.precedence() < min {
+            write!(f, "(")?;
+            self.fmt_with_precedence(f, 0)?;
+            return write!(f, ")");
+        }
+        match self {
+            RegexExpr::Literal(c) if META_CHARS.contains(*c) => write!(f, "\\{}", c),
+            RegexExpr::Literal(c) => write!(f, "{}", c),
+            RegexExpr::Concat(a, b) => {
+                a.fmt_with_precedence(f, 1)?;
+                b.fmt_with_precedence(f, 1)
+            }
+            RegexExpr::Alt(a, b) => {
+                a.fmt_with_precedence(f, 0)?;
+                write!(f, "|")?;
+                b.fmt_with_precedence(f, 0)
+            }
+            RegexExpr::Star(a) => {
+                a.fmt_with_precedence(f, 3)?;
+                write!(f, "*")
+            }
+            RegexExpr::Epsilon => write!(f, "()")
+        }
+    }
+}
+
+impl fmt::Display for RegexExpr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_with_precedence(f, 0)
+    }
+}
+
+impl NFA {
+    /// # 正規表現の文字列からNFAを生成する
+    ///
+    /// ## note
+    /// - RegexExpr::parseで解析する. to_regexの結果を与えると同じ言語を受理するNFAとなる
+    /// - "∅"であれば何も受理しないNFAを生成する
+    ///
+    /// ## args
+    /// - pattern: &str => 正規表現
+    ///
+    /// ## returns
+    /// Result<NFA, RegexParseError>
+    pub fn from_regex(pattern: &str) -> Result<NFA, RegexParseError> {
+        match RegexExpr::parse(pattern)? {
+            Some(expr) => expr.to_nfa(),
+            None => NFA::new(0, 1)
+        }.map_err(RegexParseError::Build)
+    }
+
+    /// # 受理する言語を表す正規表現の構文木を生成する
+    ///
+    /// ## note
+    /// - 状態除去法による. 新しい開始状態と受理状態を追加し, それ以外の状態を番号の小さい順に除去する
+    /// - 何も受理しない場合はNone
+    ///
+    /// ## returns
+    /// Option<RegexExpr>
+    pub fn to_regex_expr(&self) -> Option<RegexExpr> {
+        let table = self.to_transition_table();
+        let mut states: BTreeSet<i32> = BTreeSet::new();
+        states.insert(self.start);
+        states.insert(self.finish);
+        for (state_f, state_t, _) in &table {
+            states.insert(*state_f);
+            states.insert(*state_t);
+        }
+        let (start, finish) = (states.iter().last().unwrap() + 1, states.iter().last().unwrap() + 2);
+
+        // 辺ごとに正規表現をまとめる
+        let mut edges: BTreeMap<(i32, i32), RegexExpr> = BTreeMap::new();
+        let add_edge = |edges: &mut BTreeMap<(i32, i32), RegexExpr>, key: (i32, i32), expr: RegexExpr| {
+            let expr = match edges.remove(&key) {
+                Some(old) => RegexExpr::alt(old, expr),
+                None => expr
+            };
+            edges.insert(key, expr);
+        };
+        add_edge(&mut edges, (start, self.start), RegexExpr::Epsilon);
+        add_edge(&mut edges, (self.finish, finish), RegexExpr::Epsilon);
+        for (state_f, state_t, c) in table {
+            let expr = if c == '@' { RegexExpr::Epsilon } else { RegexExpr::Literal(c) };
+            add_edge(&mut edges, (state_f, state_t), expr);
+        }
+
+        // 状態除去
+        for state in states {
+            let self_loop = edges.remove(&(state, state)).map(RegexExpr::star);
+            let incoming: Vec<(i32, RegexExpr)> = edges.iter()
+                .filter(|((_, state_t), _)| *state_t == state)
+                .map(|((state_f, _), expr)| (*state_f, expr.clone()))
+                .collect();
+            let outgoing: Vec<(i32, RegexExpr)> = edges.iter()
+                .filter(|((state_f, _), _)| *state_f == state)
+                .map(|((_, state_t), expr)| (*state_t, expr.clone()))
+                .collect();
+            edges.retain(|(state_f, state_t), _| *state_f != state && *state_t != state);
+            for (state_f, expr_in) in &incoming {
+                for (state_t, expr_out) in &outgoing {
+                    let mut expr = expr_in.clone();
+                    if let Some(self_loop) = &self_loop {
+                        expr = RegexExpr::concat(expr, self_loop.clone());
+                    }
+                    expr = RegexExpr::concat(expr, expr_out.clone());
+                    add_edge(&mut edges, (*state_f, *state_t), expr);
+                }
+            }
+        }
+        edges.remove(&(start, finish))
+    }
+
+    /// # 受理する言語を表す正規表現を生成する
+    ///
+    /// ## note
+    /// - to_regex_exprの結果を文字列にしたもの. 空文字列は"()"と表す
+    /// - 何も受理しない場合は"∅"
+    /// - メタ文字 (\()|*+?[]{}.^$∅) は\でエスケープする. NFA::from_regexで読み戻せる
+    ///
+    /// ## returns
+    /// String
+    pub fn to_regex(&self) -> String {
+        match self.to_regex_expr() {
+            Some(expr) => expr.to_string(),
+            None => "∅".to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ NFA, RegexExpr, RegexParseError };
+    use crate::automaton::nfa::abb_nfa;
+
+    #[test]
+    fn test_display() {
+        let expr = RegexExpr::Concat(
+            Box::new(RegexExpr::Star(Box::new(RegexExpr::Alt(
+                Box::new(RegexExpr::Literal('a')),
+                Box::new(RegexExpr::Literal('*'))
+            )))),
+            Box::new(RegexExpr::Alt(Box::new(RegexExpr::Epsilon), Box::new(RegexExpr::Literal('b'))))
+        );
+        assert_eq!(expr.to_string(), "(a|\\*)*(()|b)");
+    }
+
+    #[test]
+    fn test_to_regex() {
+        assert_eq!(NFA::for_literal("abc").ok().unwrap().to_regex(), "abc");
+        let nfa = NFA::union(NFA::for_literal("a").ok().unwrap(), NFA::for_literal("bc").ok().unwrap()).ok().unwrap();
+        assert_eq!(nfa.to_regex(), "a|bc");
+        assert_eq!(NFA::new(0, 1).ok().unwrap().to_regex(), "∅");
+
+        let nfa = abb_nfa();
+        let nfas = vec![
+            nfa,
+            NFA::for_integer_range(7, 123),
+            NFA::for_git_short_hash(2, 3).ok().unwrap(),
+            NFA::quantifier(NFA::for_literal("ab").ok().unwrap(), 1, None).ok().unwrap(),
+            NFA::new(0, 0).ok().unwrap(),
+            NFA::new(0, 1).ok().unwrap()
+        ];
+        for nfa in nfas {
+            let round_trip = NFA::from_regex(&nfa.to_regex()).ok().unwrap();
+            assert!(round_trip.equivalent(&nfa));
+        }
+    }
+
+    #[test]
+    fn test_from_regex() {
+        let nfa = NFA::from_regex("(a|\\*)*(()|b)").ok().unwrap();
+        assert!(nfa.simulate("a*a".to_string()));
+        assert!(nfa.simulate("*b".to_string()));
+        assert!(nfa.simulate("".to_string()));
+        assert!(!nfa.simulate("bb".to_string()));
+
+        let nfa = NFA::for_literal("(∅)|\\").ok().unwrap();
+        assert_eq!(nfa.to_regex(), "\\(\\∅\\)\\|\\\\");
+        assert!(NFA::from_regex(&nfa.to_regex()).ok().unwrap().equivalent(&nfa));
+
+        let empty = NFA::from_regex("∅").ok().unwrap();
+        assert!(empty.equivalent(&NFA::new(0, 1).ok().unwrap()));
+        assert!(!empty.simulate("".to_string()));
+        assert!(NFA::from_regex("()").ok().unwrap().simulate("".to_string()));
+
+        assert_eq!(RegexExpr::parse("a+"), Err(RegexParseError::UnexpectedChar { position: 1, character: '+' }));
+        assert_eq!(RegexExpr::parse("a)"), Err(RegexParseError::UnexpectedChar { position: 1, character: ')' }));
+        assert_eq!(RegexExpr::parse("*a"), Err(RegexParseError::UnexpectedChar { position: 0, character: '*' }));
+        assert_eq!(RegexExpr::parse("a|"), Err(RegexParseError::UnexpectedEnd));
+        assert_eq!(RegexExpr::parse("(a"), Err(RegexParseError::UnexpectedEnd));
+        assert_eq!(RegexExpr::parse("a\\"), Err(RegexParseError::UnexpectedEnd));
+        assert_eq!(RegexExpr::parse(""), Err(RegexParseError::UnexpectedEnd));
+        assert_eq!(RegexExpr::parse("a@b"), Err(RegexParseError::ReservedChar { position: 1, character: '@' }));
+        assert_eq!(NFA::from_regex("(\\@)*").err(), Some(RegexParseError::ReservedChar { position: 2, character: '@' }));
+    }
+}
@@ -0,0 +1,201 @@
+//! # NFAをバイトコードに変換して実行する仮想機械 (Pike VM)
+
+#[cfg(not(feature = "std"))]
+use alloc::{ vec, vec::Vec };
+use super::nfa::NFA;
+
+/// # 仮想機械の命令
+///
+/// ## variants
+/// - Match => 入力を読み終えていれば受理する
+/// - Char(char) => 次の文字が一致すれば次の命令へ進む. 一致しなければそのスレッドは終了する
+/// - Split(usize, usize) => 2つの命令位置へスレッドを分岐させる
+/// - Jump(usize) => 指定した命令位置へ移動する
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Match,
+    Char(char),
+    Split(usize, usize),
+    Jump(usize),
+}
+
+/// # 命令位置をスレッドの一覧に加える (Jump, Splitは辿った先の命令位置を加える)
+fn add_thread(program: &[Instruction], list: &mut Vec<usize>, visited: &mut [bool], pc: usize) {
+    let mut stack = vec![pc];
+    while let Some(pc) = stack.pop() {
+        if pc >= program.len() || visited[pc] {
+            continue;
+        }
+        visited[pc] = true;
+        match program[pc] {
+            Instruction::Jump(to) => stack.push(to),
+            Instruction::Split(a, b) => {
+                stack.push(b);
+                stack.push(a);
+            }
+            _ => list.push(pc)
+        }
+    }
+}
+
+/// # バイトコードを実行する
+///
+/// ## note
+/// 読み進めた位置ごとに到達し得る命令位置の一覧を持つため, 入力長と命令数の積に比例する時間で終わる
+///
+/// ## args
+/// - program: &[Instruction] => NFA::compile_to_bytecodeで生成した命令列
+/// - input: &str => 対象文字列
+///
+/// ## returns
+/// bool
+pub fn execute(program: &[Instruction], input: &str) -> bool {
+    let mut visited = vec![false; program.len()];
+    let mut threads: Vec<usize> = vec![];
+    add_thread(program, &mut threads, &mut visited, 0);
+    for c in input.chars() {
+        if threads.is_empty() {
+            return false;
+        }
+        visited.iter_mut().for_each(|v| *v = false);
+        let mut next_threads: Vec<usize> = vec![];
+        for pc in &threads {
+            if program[*pc] == Instruction::Char(c) {
+                add_thread(program, &mut next_threads, &mut visited, pc + 1);
+            }
+        }
+        threads = next_threads;
+    }
+    threads.iter().any(|pc| program[*pc] == Instruction::Match)
+}
+
+/* 自身を引数にとるメソッド群 */
+impl NFA {
+    /// # 同じ言語を受理するバイトコードを生成する
+    ///
+    /// ## note
+    /// - 到達できない状態と受理状態へ到達できない状態は取り除く (trim)
+    /// - 状態ごとに命令のまとまりを並べる (先頭は開始状態). 各まとまりは遷移の数だけSplitで分岐し,
+    ///   文字遷移はChar(c), Jump(遷移先), ε遷移はJump(遷移先), 受理状態はMatchとなる
+    /// - 遷移のない状態は自身へのJumpとなる (そのスレッドは消える)
+    ///
+    /// ## returns
+    /// Vec<Instruction>
+    pub fn compile_to_bytecode(&self) -> Vec<Instruction> {
+        let nfa = self.trim();
+        let table = nfa.to_transition_table();
+        let mut others = vec![nfa.finish];
+        for (state_f, state_t, _) in &table {
+            others.push(*state_f);
+            others.push(*state_t);
+        }
+        others.sort();
+        others.dedup();
+        let mut states = vec![nfa.start];
+        states.extend(others.into_iter().filter(|state| *state != nfa.start));
+
+        // 状態ごとの選択肢の一覧 (文字, 遷移先). Noneは受理
+        let alternatives: Vec<Vec<Option<(char, i32)>>> = states.iter()
+            .map(|state| {
+                let mut alts: Vec<Option<(char, i32)>> = table.iter()
+                    .filter(|(state_f, _, _)| state_f == state)
+                    .map(|(_, state_t, c)| Some((*c, *state_t)))
+                    .collect();
+                if *state == nfa.finish {
+                    alts.push(None);
+                }
+                alts
+            })
+            .collect();
+
+        // 各まとまりの先頭位置を求める
+        let alt_len = |alt: &Option<(char, i32)>| match alt {
+            Some(('@', _)) | None => 1,
+            Some(_) => 2
+        };
+        let mut block_pc = vec![0; states.len()];
+        let mut pc = 0;
+        for (idx, alts) in alternatives.iter().enumerate() {
+            block_pc[idx] = pc;
+            pc += match alts.len() {
+                0 => 1,
+                n => n - 1 + alts.iter().map(alt_len).sum::<usize>()
+            };
+        }
+        let target_pc = |state: i32| block_pc[states.iter().position(|s| *s == state).unwrap()];
+
+        let mut program: Vec<Instruction> = Vec::with_capacity(pc);
+        for (idx, alts) in alternatives.iter().enumerate() {
+            if alts.is_empty() {
+                program.push(Instruction::Jump(block_pc[idx]));
+                continue;
+            }
+            let mut alt_pc = block_pc[idx] + alts.len() - 1;
+            let mut alt_pcs = vec![];
+            for alt in alts {
+                alt_pcs.push(alt_pc);
+                alt_pc += alt_len(alt);
+            }
+            for i in 0..alts.len() - 1 {
+                let rest = if i + 2 < alts.len() { block_pc[idx] + i + 1 } else { alt_pcs[i + 1] };
+                program.push(Instruction::Split(alt_pcs[i], rest));
+            }
+            for alt in alts {
+                match alt {
+                    None => program.push(Instruction::Match),
+                    Some(('@', state_t)) => program.push(Instruction::Jump(target_pc(*state_t))),
+                    Some((c, state_t)) => {
+                        program.push(Instruction::Char(*c));
+                        program.push(Instruction::Jump(target_pc(*state_t)));
+                    }
+                }
+            }
+        }
+        program
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ execute, Instruction, NFA };
+    use crate::automaton::nfa::abb_nfa;
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_compile_to_bytecode() {
+        let mut nfa = NFA::new(0, 2).ok().unwrap();       // abまたは空文字列を受理するNFA
+        nfa.set_chain(0, 1, 'a');
+        nfa.set_chain(1, 2, 'b');
+        nfa.set_chain(0, 2, '@');
+        nfa.set_chain(0, 3, 'c');
+        assert_eq!(nfa.compile_to_bytecode(), vec![
+            Instruction::Split(1, 3),
+            Instruction::Char('a'),
+            Instruction::Jump(4),
+            Instruction::Jump(6),
+            Instruction::Char('b'),
+            Instruction::Jump(6),
+            Instruction::Match
+        ]);
+        assert_eq!(NFA::new(0, 1).ok().unwrap().compile_to_bytecode(), vec![Instruction::Jump(0), Instruction::Match]);
+        assert!(!execute(&NFA::new(0, 1).ok().unwrap().compile_to_bytecode(), ""));
+    }
+
+    #[test]
+    fn test_execute() {
+        let nfa = abb_nfa();
+        let program = nfa.compile_to_bytecode();
+        let targets = ["abb", "aabb", "babb", "ababb", "", "ab", "abab", "abbb", "abba", "ccabb", "bbbbbbbabb"];
+        for target in &targets {
+            assert_eq!(execute(&program, target), nfa.simulate(target.to_string()));
+        }
+        assert!(execute(&program, "abababb"));
+        assert!(!execute(&program, "abababa"));
+
+        let nfa = NFA::for_integer_range(7, 123);
+        let program = nfa.compile_to_bytecode();
+        for n in 0..200 {
+            assert_eq!(execute(&program, &n.to_string()), nfa.simulate(n.to_string()));
+        }
+    }
+}
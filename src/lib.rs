@@ -1,3 +1,15 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
 pub mod automaton {
+    pub mod minimize;
     pub mod nfa;
-}
\ No newline at end of file
+    pub mod pattern;
+    pub mod regex_expr;
+    pub mod vm;
+}
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -7,7 +7,7 @@ fn main() -> Result<(), NFAError> {
         (0, 0, 'a'), (0, 0, 'b'), (0, 1, 'a'), (1, 1, 'a'), (1, 1, 'b'),
         (1, 2, 'a'), (2, 2, 'a'), (2, 2, 'b'), (2, 3, 'b'), (2, 0, '@')
     ];
-    let mut nfa = NFA::new(0, 3);
+    let mut nfa = NFA::new(0, 3)?;
     for chain in &chains {
         nfa.set_chain(chain.0, chain.1, chain.2)?;
     }
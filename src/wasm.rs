@@ -0,0 +1,80 @@
+//! # JavaScriptからNFAを構築, シミュレートするためのWebAssemblyバインディング
+
+use wasm_bindgen::prelude::*;
+use crate::automaton::nfa::NFA;
+
+/// # JavaScriptに公開するNFAのラッパー
+///
+/// ## members
+/// - inner: NFA => ラップしているNFA
+#[wasm_bindgen]
+pub struct WasmNFA {
+    inner: NFA
+}
+
+#[wasm_bindgen]
+impl WasmNFA {
+    /// # 開始状態と受理状態を指定してNFAを生成する
+    ///
+    /// ## args
+    /// - start: i32 => 開始状態
+    /// - finish: i32 => 受理状態
+    ///
+    /// ## returns
+    /// Result<WasmNFA, JsValue> => 失敗した場合はNFAErrorのメッセージ
+    #[wasm_bindgen(constructor)]
+    pub fn new(start: i32, finish: i32) -> Result<WasmNFA, JsValue> {
+        NFA::new(start, finish)
+            .map(|inner| WasmNFA { inner })
+            .map_err(|err| JsValue::from_str(&err.to_string()))
+    }
+
+    /// # 状態S1と状態S2を文字Cで繋ぐ
+    ///
+    /// ## note
+    /// - JavaScriptにはchar型がないため, 文字は1文字の文字列で受け取る
+    /// - ε = "@"
+    ///
+    /// ## args
+    /// - from: i32 => 状態S1
+    /// - to: i32 => 状態S2
+    /// - c: &str => 文字C (1文字の文字列)
+    ///
+    /// ## returns
+    /// Result<(), JsValue> => 1文字でない場合やNFAErrorの場合はそのメッセージ
+    pub fn set_chain(&mut self, from: i32, to: i32, c: &str) -> Result<(), JsValue> {
+        let mut chars = c.chars();
+        let c = match (chars.next(), chars.next()) {
+            (Some(c), None) => c,
+            _ => return Err(JsValue::from_str("c must be a single character"))
+        };
+        self.inner.set_chain(from, to, c).map_err(|err| JsValue::from_str(&err.to_string()))
+    }
+
+    /// # 文字列を受理するかシミュレートする
+    ///
+    /// ## args
+    /// - target: &str => 対象文字列
+    ///
+    /// ## returns
+    /// bool
+    pub fn simulate(&self, target: &str) -> bool {
+        self.inner.simulate(target.to_string())
+    }
+
+    /// # 管理している状態の数を返す
+    ///
+    /// ## returns
+    /// usize
+    pub fn state_count(&self) -> usize {
+        self.inner.state_count()
+    }
+
+    /// # Graphviz (DOT言語) 形式で書き出す
+    ///
+    /// ## returns
+    /// String
+    pub fn to_dot(&self) -> String {
+        self.inner.to_dot()
+    }
+}
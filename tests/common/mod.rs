@@ -0,0 +1,21 @@
+use regex_executor::automaton::nfa::NFA;
+
+/// # (a|b)* abbを受理するテスト用のNFA
+#[allow(unused_must_use)]
+pub fn abb_nfa() -> NFA {
+    let mut nfa = NFA::new(0, 10).ok().unwrap();
+    nfa.set_chain(0, 7, '@');
+    nfa.set_chain(0, 1, '@');
+    nfa.set_chain(1, 2, '@');
+    nfa.set_chain(1, 4, '@');
+    nfa.set_chain(2, 3, 'a');
+    nfa.set_chain(3, 6, '@');
+    nfa.set_chain(4, 5, 'b');
+    nfa.set_chain(5, 6, '@');
+    nfa.set_chain(6, 1, '@');
+    nfa.set_chain(6, 7, '@');
+    nfa.set_chain(7, 8, 'a');
+    nfa.set_chain(8, 9, 'b');
+    nfa.set_chain(9, 10, 'b');
+    nfa
+}
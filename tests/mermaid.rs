@@ -0,0 +1,22 @@
+extern crate regex_executor;
+mod common;
+
+#[test]
+fn mermaid_renders_abb_nfa() {
+    let nfa = common::abb_nfa();
+
+    let mermaid = nfa.to_mermaid();
+    let lines: Vec<&str> = mermaid.lines().map(|line| line.trim()).collect();
+    assert_eq!(lines[0], "stateDiagram-v2");
+    for state in 0..=10 {
+        assert!(lines.contains(&format!("state \"{}\" as s{}", state, state).as_str()));
+    }
+    assert!(lines.contains(&"[*] --> s0"));
+    assert!(lines.contains(&"s10 --> [*]"));
+    assert!(lines.contains(&"class s0 start"));
+    assert!(lines.contains(&"class s10 finish"));
+    assert!(lines.contains(&"s2 --> s3 : a"));
+    assert!(lines.contains(&"s9 --> s10 : b"));
+    assert!(lines.contains(&"s6 --> s1 : ε"));
+    assert_eq!(lines.iter().filter(|line| line.contains(" --> s") && line.contains(" : ")).count(), 13);
+}
@@ -0,0 +1,53 @@
+extern crate regex_executor;
+use proptest::prelude::*;
+use regex_executor::automaton::nfa::{ NFA, ProductAccept };
+
+/// # 状態数の上限 (状態0が開始状態, 状態STATES-1が受理状態)
+const STATES: i32 = 5;
+
+/// # 小さな状態数の範囲でランダムな遷移表を持つNFAを生成する
+fn arb_nfa() -> impl Strategy<Value = NFA> {
+    let chain = (0..STATES, 0..STATES, prop::sample::select(vec!['a', 'b', '@']));
+    prop::collection::vec(chain, 0..12)
+        .prop_map(|table| NFA::from_transition_table(0, STATES - 1, &table).ok().unwrap())
+}
+
+/// # NFAに与える入力文字列を生成する
+fn arb_input() -> impl Strategy<Value = String> {
+    "[ab]{0,6}"
+}
+
+proptest! {
+    #[test]
+    fn reverse_twice_is_equivalent(nfa in arb_nfa()) {
+        prop_assert!(nfa.reverse().reverse().equivalent(&nfa));
+    }
+
+    #[test]
+    fn reverse_accepts_reversed_input(nfa in arb_nfa(), s in arb_input()) {
+        let reversed: String = s.chars().rev().collect();
+        prop_assert_eq!(nfa.reverse().simulate(reversed), nfa.simulate(s));
+    }
+
+    #[test]
+    fn intersection_accepts_iff_both_accept(a in arb_nfa(), b in arb_nfa(), s in arb_input()) {
+        let both = NFA::product_construction(&a, &b, ProductAccept::Both).ok().unwrap();
+        prop_assert_eq!(both.simulate(s.clone()), a.simulate(s.clone()) && b.simulate(s));
+    }
+
+    #[test]
+    fn to_dfa_and_minimize_accept_same_input(nfa in arb_nfa(), s in arb_input()) {
+        let dfa = nfa.to_dfa().ok().unwrap();
+        let minimized = nfa.minimize_states().ok().unwrap();
+        prop_assert_eq!(dfa.simulate(s.clone()), nfa.simulate(s.clone()));
+        prop_assert_eq!(minimized.simulate(s.clone()), nfa.simulate(s));
+        prop_assert!(minimized.state_count() <= dfa.trim().state_count());
+    }
+
+    #[test]
+    fn transition_table_round_trip_is_equivalent(nfa in arb_nfa()) {
+        let table = nfa.to_transition_table();
+        let round_trip = NFA::from_transition_table(nfa.start, nfa.finish, &table).ok().unwrap();
+        prop_assert!(round_trip.equivalent(&nfa));
+    }
+}
@@ -0,0 +1,35 @@
+// WasmNFAをJavaScriptから利用するテスト
+//
+// cargo rustc --lib --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+// wasm-bindgen --target nodejs --out-dir pkg target/wasm32-unknown-unknown/release/regex_executor.wasm
+// node --test tests/wasm/
+
+const assert = require("node:assert");
+const test = require("node:test");
+const { WasmNFA } = require("../../pkg/regex_executor.js");
+
+test("(a|b)* abbを受理するNFA", () => {
+    const nfa = new WasmNFA(0, 10);
+    const chains = [
+        [0, 7, "@"], [0, 1, "@"], [1, 2, "@"], [1, 4, "@"], [2, 3, "a"], [3, 6, "@"], [4, 5, "b"],
+        [5, 6, "@"], [6, 1, "@"], [6, 7, "@"], [7, 8, "a"], [8, 9, "b"], [9, 10, "b"]
+    ];
+    for (const [from, to, c] of chains) {
+        nfa.set_chain(from, to, c);
+    }
+    assert.strictEqual(nfa.state_count(), 11);
+    for (const target of ["abb", "aabb", "babb", "ababb", "bbbbbbbabb"]) {
+        assert.strictEqual(nfa.simulate(target), true, target);
+    }
+    for (const target of ["", "ab", "abab", "abbb", "abba", "ccabb"]) {
+        assert.strictEqual(nfa.simulate(target), false, target);
+    }
+    assert.ok(nfa.to_dot().startsWith("digraph"));
+});
+
+test("不正な引数はエラーになる", () => {
+    const nfa = new WasmNFA(0, 1);
+    assert.throws(() => nfa.set_chain(0, 1, "ab"), /single character/);
+    assert.throws(() => nfa.set_chain(0, 5, "a"), /not reserved/);
+    assert.throws(() => new WasmNFA(0, 5000), /out of range/);
+});